@@ -0,0 +1,630 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::Hotkey;
+
+use super::{AppError, Eligibility, MatchRule, Query};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Action {
+    // identify the action when merging configure layers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub hotkey: Hotkey,
+    #[serde(flatten)]
+    pub kind: ActionKind,
+}
+
+// what a hotkey does, selected by the `kind` field
+// like:
+// { "hotkey": "ctrl + 1", "kind": "focus", "target": "browser" }
+// { "hotkey": "ctrl + 2", "kind": "launch", "exec": "C:/code.exe", "args": ["--new-window"] }
+// { "hotkey": "ctrl + 3", "kind": "switch_profile", "profile": "gaming" }
+// { "hotkey": "ctrl + 4", "kind": "switch_profile", "profile": null }
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ActionKind {
+    // bring the first window of target to front
+    Focus {
+        #[serde(flatten)]
+        target: Target,
+        #[serde(flatten)]
+        focus: FocusOptions,
+    },
+    // start target application
+    Launch {
+        #[serde(flatten)]
+        target: Target,
+        #[serde(flatten)]
+        launch: Launch,
+    },
+    // focus target, start it if it has no window
+    LaunchOrFocus {
+        #[serde(flatten)]
+        target: Target,
+        #[serde(flatten)]
+        launch: Launch,
+        #[serde(flatten)]
+        focus: FocusOptions,
+    },
+    // run a command without looking for windows
+    Run {
+        command: String,
+        #[serde(flatten)]
+        launch: Launch,
+    },
+    // close, minimize or maximize a window of target, the foreground window if no target
+    Close {
+        #[serde(flatten)]
+        target: Target,
+    },
+    Minimize {
+        #[serde(flatten)]
+        target: Target,
+    },
+    Maximize {
+        #[serde(flatten)]
+        target: Target,
+    },
+    // focus target, minimize it if it is already the foreground window
+    Toggle {
+        #[serde(flatten)]
+        target: Target,
+    },
+    // press key combinations one after another
+    SendKeys {
+        keys: Vec<Hotkey>,
+    },
+    // open a file, directory or url with its default application
+    Open {
+        path: String,
+    },
+    // null switches back to the base actions without profile
+    SwitchProfile {
+        profile: Option<String>,
+    },
+    // focus the window which was focused before, like back and forward of a
+    // browser, whatever app it belongs to
+    Back,
+    Forward,
+    // put the foreground window into a slot, `title` is a regex preferring
+    // windows of the same app once the marked window is gone, its whole
+    // title if not set
+    // like:
+    // { "hotkey": "ctrl + alt + 1", "kind": "mark", "slot": 1 }
+    // { "hotkey": "alt + 1", "kind": "jump_to_mark", "slot": 1 }
+    Mark {
+        slot: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+    },
+    // focus the window in a slot
+    JumpToMark {
+        slot: u32,
+    },
+    // choose a window from a list filtered by typing, in a console window
+    Pick,
+    // focus the Nth window or app of `numbered`, generated from it
+    FocusNumber {
+        number: usize,
+    },
+    // read configure files again
+    Reload,
+}
+
+impl ActionKind {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            ActionKind::Focus { .. } => "focus",
+            ActionKind::Launch { .. } => "launch",
+            ActionKind::LaunchOrFocus { .. } => "launch_or_focus",
+            ActionKind::Run { .. } => "run",
+            ActionKind::Close { .. } => "close",
+            ActionKind::Minimize { .. } => "minimize",
+            ActionKind::Maximize { .. } => "maximize",
+            ActionKind::Toggle { .. } => "toggle",
+            ActionKind::SendKeys { .. } => "send_keys",
+            ActionKind::Open { .. } => "open",
+            ActionKind::SwitchProfile { .. } => "switch_profile",
+            ActionKind::Back => "back",
+            ActionKind::Forward => "forward",
+            ActionKind::Mark { .. } => "mark",
+            ActionKind::JumpToMark { .. } => "jump_to_mark",
+            ActionKind::Pick => "pick",
+            ActionKind::FocusNumber { .. } => "focus_number",
+            ActionKind::Reload => "reload",
+        }
+    }
+
+    pub fn get_target(&self) -> Option<&Target> {
+        match self {
+            ActionKind::Focus { target, .. }
+            | ActionKind::Launch { target, .. }
+            | ActionKind::LaunchOrFocus { target, .. }
+            | ActionKind::Close { target }
+            | ActionKind::Minimize { target }
+            | ActionKind::Maximize { target }
+            | ActionKind::Toggle { target } => Some(target),
+            _ => None,
+        }
+    }
+}
+
+// windows an action works on, an executable path, an app defined in `apps`,
+// match rules or a query, `exec` is still used to launch when combined with
+// `match` or `query`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Target {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exec: Option<String>,
+    // name of an app defined in `apps`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(rename = "match", default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<MatchRule>,
+    // like `exe:code.exe title~ektox`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    // overrides the global eligibility for this action
+    #[serde(default, skip_serializing_if = "Eligibility::is_empty")]
+    pub eligibility: Eligibility,
+}
+
+impl Target {
+    pub fn exec(exec: &str) -> Self {
+        Target {
+            exec: Some(exec.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn app(name: &str) -> Self {
+        Target {
+            target: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn rule(rule: MatchRule) -> Self {
+        Target {
+            rule: Some(rule),
+            ..Default::default()
+        }
+    }
+
+    pub fn query(query: &str) -> Self {
+        Target {
+            query: Some(query.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.exec.is_none() && self.target.is_none() && self.rule.is_none() && self.query.is_none()
+    }
+
+    // something to start when target has no window
+    pub fn is_launchable(&self) -> bool {
+        self.exec.is_some() || self.target.is_some()
+    }
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(query) = &self.query {
+            return write!(f, "query '{}'", query);
+        }
+        match (&self.exec, &self.target, &self.rule) {
+            (_, _, Some(rule)) => write!(
+                f,
+                "match {}",
+                serde_json::to_string(rule).unwrap_or_default()
+            ),
+            (Some(exec), _, None) => write!(f, "{}", exec),
+            (None, Some(target), None) => write!(f, "app '{}'", target),
+            (None, None, None) => write!(f, "foreground window"),
+        }
+    }
+}
+
+// how to start a process
+// like:
+// { "kind": "launch_or_focus", "exec": "C:/code.exe", "args": ["-n"], "cwd": "~/work", "env": { "LANG": "C" } }
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Launch {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    // working directory, directory of ektox if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    // added to the environment of ektox
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    // focus the first window once it appears, pressing the hotkey again
+    // while waiting does not start another process, without waiting it does
+    // not for a short while
+    #[serde(
+        default = "Launch::default_wait",
+        skip_serializing_if = "Launch::is_default_wait"
+    )]
+    pub wait: bool,
+    // milliseconds to wait for the first window
+    #[serde(
+        default = "Launch::default_timeout",
+        skip_serializing_if = "Launch::is_default_timeout"
+    )]
+    pub timeout: u64,
+}
+
+impl Launch {
+    fn default_wait() -> bool {
+        true
+    }
+
+    fn is_default_wait(wait: &bool) -> bool {
+        *wait == Self::default_wait()
+    }
+
+    fn default_timeout() -> u64 {
+        10000
+    }
+
+    fn is_default_timeout(timeout: &u64) -> bool {
+        *timeout == Self::default_timeout()
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout)
+    }
+}
+
+impl Default for Launch {
+    fn default() -> Self {
+        Launch {
+            args: Vec::new(),
+            cwd: None,
+            env: BTreeMap::new(),
+            wait: Self::default_wait(),
+            timeout: Self::default_timeout(),
+        }
+    }
+}
+
+// how focusing picks a window of target and what it does when target is already foreground
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct FocusOptions {
+    #[serde(default, skip_serializing_if = "Select::is_default")]
+    pub select: Select,
+    #[serde(default, skip_serializing_if = "OnActive::is_default")]
+    pub on_active: OnActive,
+    #[serde(flatten)]
+    pub cycle: Cycle,
+}
+
+// which window is focused first when several match, the others follow in
+// this order when cycling, ties keep the most recently used order
+// like:
+// { "select": "largest" }
+// { "select": { "prefer_title": "ektox" } }
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Select {
+    #[default]
+    MostRecentlyUsed,
+    // on the monitor of the foreground window
+    CurrentMonitor,
+    Largest,
+    // in the order ektox first saw the windows
+    Oldest,
+    Newest,
+    // the window this binding focused last time
+    LastFocused,
+    // windows with a title matching the regex
+    PreferTitle(String),
+}
+
+impl Select {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+// what focusing does when a window of target is already foreground
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OnActive {
+    // focus the next window of target
+    #[default]
+    Cycle,
+    Minimize,
+    // focus the window which was foreground before target was brought forward
+    ReturnToPrevious,
+    Nothing,
+}
+
+impl OnActive {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+// how repeated presses walk through the windows of target, most recently used first
+// like:
+// { "hotkey": "alt + 1", "kind": "focus", "target": "code" }
+// { "hotkey": "alt + shift + 1", "kind": "focus", "target": "code", "reverse": true }
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    // walk from the least recently used window backwards
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reverse: bool,
+    // milliseconds after the last press when cycling starts over from the
+    // most recently used window
+    #[serde(
+        default = "Cycle::default_cycle_timeout",
+        skip_serializing_if = "Cycle::is_default_cycle_timeout"
+    )]
+    pub cycle_timeout: u64,
+}
+
+impl Cycle {
+    fn default_cycle_timeout() -> u64 {
+        1500
+    }
+
+    fn is_default_cycle_timeout(timeout: &u64) -> bool {
+        *timeout == Self::default_cycle_timeout()
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        Duration::from_millis(self.cycle_timeout)
+    }
+}
+
+impl Default for Cycle {
+    fn default() -> Self {
+        Cycle {
+            reverse: false,
+            cycle_timeout: Self::default_cycle_timeout(),
+        }
+    }
+}
+
+impl Action {
+    pub fn validate(&self) -> Result<(), AppError> {
+        let error = |message: &str| {
+            Err(AppError::new(format!(
+                "Action '{}' ({}) {}",
+                self.hotkey,
+                self.kind.get_name(),
+                message
+            )))
+        };
+        if let Some(target) = self.kind.get_target() {
+            if target.exec.is_some() && target.target.is_some() {
+                return error("must not have both 'exec' and 'target'");
+            }
+            let finders = [
+                target.target.is_some(),
+                target.rule.is_some(),
+                target.query.is_some(),
+            ];
+            if finders.into_iter().filter(|defined| *defined).count() > 1 {
+                return error("must have only one of 'target', 'match' or 'query'");
+            }
+            if let Some(query) = &target.query {
+                if let Err(message) = Query::parse(query) {
+                    return error(&message.to_string());
+                }
+            }
+            if let Some(rule) = &target.rule {
+                if rule.is_empty() {
+                    return error("must not have an empty 'match'");
+                }
+                if let Err(message) = rule.compile() {
+                    return error(&message.to_string());
+                }
+            }
+        }
+        if let ActionKind::Focus { focus, .. } | ActionKind::LaunchOrFocus { focus, .. } =
+            &self.kind
+        {
+            if let Select::PreferTitle(pattern) = &focus.select {
+                if let Err(message) = regex::Regex::new(pattern) {
+                    return error(&format!("has invalid 'prefer_title': {}", message));
+                }
+            }
+        }
+        match &self.kind {
+            ActionKind::Launch { target, .. } | ActionKind::LaunchOrFocus { target, .. }
+                if !target.is_launchable() =>
+            {
+                error("must have 'exec' or 'target' to launch")
+            }
+            // close, minimize and maximize fall back to the foreground window
+            ActionKind::Focus { target, .. } | ActionKind::Toggle { target }
+                if target.is_empty() =>
+            {
+                error("must have 'exec', 'target', 'match' or 'query'")
+            }
+            ActionKind::SendKeys { keys } if keys.is_empty() => error("must have at least one key"),
+            ActionKind::Mark {
+                title: Some(pattern),
+                ..
+            } => match regex::Regex::new(pattern) {
+                Ok(_) => Ok(()),
+                Err(message) => error(&format!("has invalid 'title': {}", message)),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    // fields where variables are expanded, paths, commands and arguments
+    pub fn get_paths_mut(&mut self) -> Vec<(&'static str, &mut String)> {
+        let mut paths = Vec::new();
+        let (target, launch) = match &mut self.kind {
+            ActionKind::Launch { target, launch }
+            | ActionKind::LaunchOrFocus { target, launch, .. } => (Some(target), Some(launch)),
+            ActionKind::Focus { target, .. }
+            | ActionKind::Close { target }
+            | ActionKind::Minimize { target }
+            | ActionKind::Maximize { target }
+            | ActionKind::Toggle { target } => (Some(target), None),
+            ActionKind::Run { command, launch } => {
+                paths.push(("command", command));
+                (None, Some(launch))
+            }
+            // urls may contain escapes like %20 which are not variables
+            ActionKind::Open { path } if !path.contains("://") => {
+                paths.push(("path", path));
+                (None, None)
+            }
+            _ => (None, None),
+        };
+        if let Some(exec) = target.and_then(|target| target.exec.as_mut()) {
+            paths.push(("exec", exec));
+        }
+        if let Some(launch) = launch {
+            if let Some(cwd) = launch.cwd.as_mut() {
+                paths.push(("cwd", cwd));
+            }
+            for arg in launch.args.iter_mut() {
+                paths.push(("args", arg));
+            }
+        }
+        paths
+    }
+}
+
+// turn `exec`, `target` and `switch_profile` of actions before 1.2.0 into `kind`
+pub fn migrate_action_kind(action: &mut serde_json::Map<String, serde_json::Value>) {
+    if action.contains_key("kind") {
+        return;
+    }
+    // rebuilt instead of removing `switch_profile`, to keep the order of keys
+    let mut kind = "focus";
+    for (key, value) in std::mem::take(action) {
+        if key == "switch_profile" {
+            action.insert("profile".to_string(), value);
+            kind = "switch_profile";
+        } else {
+            action.insert(key, value);
+        }
+    }
+    action.insert(
+        "kind".to_string(),
+        serde_json::Value::String(kind.to_string()),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{migrate_action_kind, Action, ActionKind, Target};
+
+    #[test]
+    fn it_works() {
+        let action: Action = serde_json::from_value(json!({
+            "hotkey": "ctrl + 1",
+            "kind": "focus",
+            "target": "browser"
+        }))
+        .unwrap();
+        action.validate().unwrap();
+        assert_eq!(action.kind.get_target(), Some(&Target::app("browser")));
+
+        let action: Action = serde_json::from_value(json!({
+            "hotkey": "ctrl + 2",
+            "kind": "launch_or_focus",
+            "exec": "C:/code.exe",
+            "args": ["--new-window"]
+        }))
+        .unwrap();
+        match &action.kind {
+            ActionKind::LaunchOrFocus { target, launch, .. } => {
+                assert_eq!(target, &Target::exec("C:/code.exe"));
+                assert_eq!(launch.args, vec!["--new-window"]);
+                assert!(launch.wait);
+                assert_eq!(launch.timeout, 10000);
+            }
+            _ => panic!("should be launch_or_focus"),
+        }
+
+        let action: Action = serde_json::from_value(json!({
+            "hotkey": "ctrl + 3",
+            "kind": "send_keys",
+            "keys": ["ctrl + c", "alt + tab"]
+        }))
+        .unwrap();
+        action.validate().unwrap();
+
+        let action: Action =
+            serde_json::from_value(json!({ "hotkey": "ctrl + 4", "kind": "reload" })).unwrap();
+        assert_eq!(action.kind.get_name(), "reload");
+    }
+
+    #[test]
+    fn serialize() {
+        let action: Action = serde_json::from_value(json!({
+            "hotkey": "ctrl + 1",
+            "kind": "switch_profile",
+            "profile": "gaming"
+        }))
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&action).unwrap(),
+            json!({ "hotkey": "Ctrl+1", "kind": "switch_profile", "profile": "gaming" })
+        );
+
+        let action: Action = serde_json::from_value(json!({
+            "hotkey": "ctrl + 1",
+            "kind": "switch_profile",
+            "profile": null
+        }))
+        .unwrap();
+        assert!(matches!(
+            action.kind,
+            ActionKind::SwitchProfile { profile: None }
+        ));
+    }
+
+    #[test]
+    fn invalid() {
+        let unknown = serde_json::from_value::<Action>(json!({
+            "hotkey": "ctrl + 1",
+            "kind": "dance"
+        }));
+        assert!(unknown.is_err());
+
+        let action: Action =
+            serde_json::from_value(json!({ "hotkey": "ctrl + 1", "kind": "focus" })).unwrap();
+        assert!(action.validate().is_err());
+
+        let action: Action = serde_json::from_value(json!({
+            "hotkey": "ctrl + 1",
+            "kind": "toggle",
+            "exec": "C:/a.exe",
+            "target": "a"
+        }))
+        .unwrap();
+        assert!(action.validate().is_err());
+    }
+
+    #[test]
+    fn migrate() {
+        let mut action = json!({ "hotkey": "ctrl + 1", "exec": "C:/a.exe" });
+        migrate_action_kind(action.as_object_mut().unwrap());
+        assert_eq!(
+            action,
+            json!({ "hotkey": "ctrl + 1", "kind": "focus", "exec": "C:/a.exe" })
+        );
+
+        let mut action = json!({ "hotkey": "ctrl + 2", "switch_profile": "gaming" });
+        migrate_action_kind(action.as_object_mut().unwrap());
+        assert_eq!(
+            action,
+            json!({ "hotkey": "ctrl + 2", "kind": "switch_profile", "profile": "gaming" })
+        );
+    }
+}
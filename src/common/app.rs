@@ -1,55 +1,145 @@
-use std::fs::File;
+use std::{fs, path::PathBuf, time::Instant};
 
 use windows::Win32::{
     Foundation::HWND,
     UI::{
-        Input::KeyboardAndMouse::RegisterHotKey,
+        Input::KeyboardAndMouse::{RegisterHotKey, UnregisterHotKey},
         WindowsAndMessaging::{
-            GetMessageW, SetForegroundWindow, ShowWindow, MSG, SW_NORMAL, SW_RESTORE, WM_HOTKEY,
-            WM_QUIT, WS_MINIMIZE,
+            GetMessageW, KillTimer, SetTimer, MSG, WM_HOTKEY, WM_QUIT, WM_TIMER,
         },
     },
 };
 
-use crate::utils::WindowFinder;
+use crate::utils::{
+    Console, MessageBox, PathExpander, Win32Backend, WindowBackend, WindowEventHook,
+};
 
-use super::{AppError, Config, Version};
+use super::{
+    is_same_path, Action, AppError, Autostart, AutostartStatus, Config, ConfigLayer, ConfigLoader,
+    Executor, LoadedFile, Outcome, Query, Version, WindowHistory, WindowMarks, WindowPicker,
+    WindowSwitcher, HISTORY_CAPACITY,
+};
 
 pub struct App {
     version: Version,
     config: Config,
+    // active profile, base profile if None
+    profile: Option<String>,
+    // actions of active profile, index is the registered hotkey id
+    actions: Vec<Action>,
+    executor: Executor,
+    // timer polling pending launches for their first window
+    timer: Option<usize>,
+    // timer applying window events while no hotkey is pressed
+    refresh_timer: Option<usize>,
+    // keeps the window registry of the executor up to date while running
+    hook: Option<WindowEventHook>,
 }
 impl App {
     pub fn init() -> Result<Self, AppError> {
         let version = Version::from_cargo_package();
-        let config = Self::load_configure()?;
-        let app = App { version, config };
+        let mut config = Self::load_configure()?;
+        config.validate()?;
+        config.expand_paths(&PathExpander::from_env())?;
+        Self::sync_autostart(config.startup);
+        let profile = config.get_default_profile().cloned();
+        let actions = config.resolve_actions(profile.as_ref())?;
+        let mut executor = Executor::new(Box::new(Win32Backend));
+        if let Some(path) = Self::history_path() {
+            executor.set_history(WindowHistory::load(&path, HISTORY_CAPACITY));
+        }
+        if let Some(path) = Self::marks_path().filter(|_| config.persist_marks) {
+            executor.set_marks(WindowMarks::load(&path));
+        }
+        let app = App {
+            version,
+            config,
+            profile,
+            actions,
+            executor,
+            timer: None,
+            refresh_timer: None,
+            hook: None,
+        };
 
-        return Ok(app);
+        Ok(app)
     }
 
-    pub fn start(&self) {
+    // failing to update the login entry should not prevent ektox from working
+    fn sync_autostart(enabled: bool) {
+        let state =
+            ConfigLayer::user().map(|layer| layer.get_path().with_file_name("autostart.state"));
+        let result = Autostart::current_command()
+            .and_then(|command| Autostart::platform(state).sync(enabled, &command));
+        match result {
+            Ok(AutostartStatus::Drift(message)) => MessageBox::info(message.as_str()),
+            Ok(_) => {}
+            Err(error) => MessageBox::error(error.to_string().as_str()),
+        }
+    }
+
+    pub fn start(&mut self) {
+        self.hook = Some(WindowEventHook::install());
+        self.refresh_timer = Some(unsafe { SetTimer(HWND::default(), 0, 1000, None) });
         self.register_hotkeys();
         self.handle_window_event()
     }
 
     pub fn register_hotkeys(&self) {
         unsafe {
-            let mut index = 0;
-            for action in self.config.get_actions() {
+            for (index, action) in self.actions.iter().enumerate() {
                 let hotkey = &action.hotkey;
                 RegisterHotKey(
                     HWND::default(),
-                    index,
+                    index as i32,
                     hotkey.get_modifiers(),
                     hotkey.get_key(),
                 );
-                index += 1;
             }
         }
     }
 
-    fn handle_window_event(&self) {
+    pub fn unregister_hotkeys(&self) {
+        unsafe {
+            for index in 0..self.actions.len() {
+                UnregisterHotKey(HWND::default(), index as i32);
+            }
+        }
+    }
+
+    pub fn switch_profile(&mut self, profile: Option<String>) -> Result<(), AppError> {
+        let actions = self.config.resolve_actions(profile.as_ref())?;
+        self.unregister_hotkeys();
+        self.profile = profile;
+        self.actions = actions;
+        self.register_hotkeys();
+        Ok(())
+    }
+
+    // read configure files again, keep the active profile if it still exists
+    pub fn reload(&mut self) -> Result<(), AppError> {
+        let mut config = Self::load_configure()?;
+        config.validate()?;
+        config.expand_paths(&PathExpander::from_env())?;
+        let profile = match &self.profile {
+            Some(name) if config.get_profile(name).is_some() => Some(name.clone()),
+            _ => config.get_default_profile().cloned(),
+        };
+        let actions = config.resolve_actions(profile.as_ref())?;
+        Self::sync_autostart(config.startup);
+        self.unregister_hotkeys();
+        self.config = config;
+        self.profile = profile;
+        self.actions = actions;
+        self.register_hotkeys();
+        Ok(())
+    }
+
+    pub fn get_profile(&self) -> Option<&String> {
+        self.profile.as_ref()
+    }
+
+    fn handle_window_event(&mut self) {
         let mut msg: MSG = MSG::default();
         unsafe {
             while GetMessageW(&mut msg, HWND::default(), 0, 0).into() {
@@ -58,6 +148,15 @@ impl App {
                         let id: usize = msg.wParam.0;
                         self.process(id);
                     }
+                    WM_TIMER if Some(msg.wParam.0) == self.refresh_timer => {
+                        self.executor.refresh(Instant::now());
+                        self.save_state();
+                    }
+                    WM_TIMER => {
+                        self.executor.poll(&self.config, Instant::now());
+                        self.update_timer();
+                        self.save_state();
+                    }
                     WM_QUIT => {
                         break;
                     }
@@ -66,38 +165,210 @@ impl App {
             }
         }
     }
-    fn process(&self, id: usize) {
-        let key = self.config.get_actions()[id].exec.to_owned();
-        let target_window: Vec<HWND> = WindowFinder::get_frontend_window()
-            .into_iter()
-            .filter(|hwnd| WindowFinder::get_process_name_from_hwnd(*hwnd) == key)
-            .collect();
-        if target_window.len() > 0 {
-            unsafe {
-                let window = target_window[0];
-                if WindowFinder::get_window_style(window) & WS_MINIMIZE.0 != 0 {
-                    ShowWindow(window, SW_NORMAL);
+    fn process(&mut self, id: usize) {
+        let action = match self.actions.get(id) {
+            Some(action) => action.clone(),
+            None => return,
+        };
+        let result = match self.executor.execute(&self.config, &action) {
+            Ok(Outcome::SwitchProfile(profile)) => self.switch_profile(profile),
+            Ok(Outcome::Reload) => self.reload(),
+            Ok(_) => Ok(()),
+            Err(error) => Err(error),
+        };
+        if let Err(error) = result {
+            MessageBox::error(error.to_string().as_str());
+        }
+        self.update_timer();
+        self.save_state();
+    }
+
+    // activation history is kept next to the user configure file, so that
+    // `ektox history` can show it
+    fn history_path() -> Option<PathBuf> {
+        ConfigLayer::user().map(|layer| layer.get_path().with_file_name("history.state"))
+    }
+
+    fn marks_path() -> Option<PathBuf> {
+        ConfigLayer::user().map(|layer| layer.get_path().with_file_name("marks.state"))
+    }
+
+    // state which cannot be written is not worth interrupting the user, the
+    // history is written as soon as it changed, so that `ektox pick` and
+    // `ektox history` never see a stale one and nothing is lost when the
+    // process is killed on logoff
+    fn save_state(&mut self) {
+        if self.executor.take_history_changed() {
+            if let Some(path) = Self::history_path() {
+                let _ = self.executor.get_history().save(&path);
+            }
+        }
+        if self.executor.take_marks_changed() && self.config.persist_marks {
+            if let Some(path) = Self::marks_path() {
+                let _ = self.executor.get_marks().save(&path);
+            }
+        }
+    }
+
+    // keep the timer running only while launched targets wait for their window
+    fn update_timer(&mut self) {
+        unsafe {
+            match (self.timer, self.executor.has_pending()) {
+                (None, true) => self.timer = Some(SetTimer(HWND::default(), 0, 200, None)),
+                (Some(timer), false) => {
+                    KillTimer(HWND::default(), timer);
+                    self.timer = None;
                 }
-                SetForegroundWindow(window);
+                _ => {}
             }
         }
     }
 
     fn load_configure() -> Result<Config, AppError> {
-        match File::open("./config.json") {
-            Ok(file) => match serde_json::from_reader::<File, Config>(file) {
-                Ok(config) => Ok(config),
-                Err(error) => Err(AppError::new(error.to_string())),
-            },
-            Err(io_error) => {
-                let msg = match io_error.kind() {
-                    std::io::ErrorKind::NotFound => "Configure File Not Found",
-                    std::io::ErrorKind::PermissionDenied => " Open Configure File Permision Denied",
-                    _ => "Unexpected error",
-                };
-                Err(AppError::new(msg.to_string()))
+        let (config, files) = ConfigLoader::with_default_layers(Config::schema_version()).load()?;
+        for file in files.iter() {
+            if file.is_writable() && file.get_report().is_required() {
+                Self::write_migrated(file)?;
             }
         }
+        Ok(config)
+    }
+
+    fn write_migrated(file: &LoadedFile) -> Result<(), AppError> {
+        let path = file.get_path();
+        let report = file.get_report();
+        // keep the original file before overwriting it with the migrated one
+        let backup = format!("{}.{}.bak", path.display(), report.get_from());
+        if let Err(error) = fs::copy(path, &backup) {
+            return Err(AppError::new(format!(
+                "Backup Configure File To '{}' Failed: {}",
+                backup, error
+            )));
+        }
+        let migrated = serde_json::to_string_pretty(report.get_migrated())
+            .map_err(|error| AppError::new(error.to_string()))?;
+        if let Err(error) = fs::write(path, migrated) {
+            return Err(AppError::new(format!(
+                "Write Migrated Configure File '{}' Failed: {}",
+                path.display(),
+                error
+            )));
+        }
+        Ok(())
+    }
+
+    // windows matching query, every switchable window if query is empty,
+    // one line per window or a json array
+    pub fn list_windows(query: &str, json: bool) -> Result<String, AppError> {
+        let backend = Win32Backend;
+        let windows = if query.trim().is_empty() {
+            backend.snapshot()
+        } else {
+            Query::parse(query)
+                .map_err(|error| AppError::new(error.to_string()))?
+                .find(&backend)
+        };
+        if json {
+            return serde_json::to_string_pretty(&windows)
+                .map_err(|error| AppError::new(error.to_string()));
+        }
+        let lines: Vec<String> = windows.iter().map(|window| window.to_string()).collect();
+        Ok(lines.join("\n"))
+    }
+
+    // recently used windows of the running instance, most recent first
+    pub fn history() -> Result<String, AppError> {
+        let path = Self::history_path()
+            .ok_or_else(|| AppError::new("User Configure Directory Not Found".to_string()))?;
+        let history = WindowHistory::load(&path, HISTORY_CAPACITY);
+        let backend = Win32Backend;
+        let lines: Vec<String> = history
+            .get_entries()
+            .iter()
+            .map(|entry| backend.describe(entry.get_hwnd()))
+            .zip(history.get_entries())
+            // handle reused by another window after the history was saved
+            .filter(|(window, entry)| is_same_path(window.get_path(), entry.get_path()))
+            .map(|(window, _)| window.to_string())
+            .collect();
+        Ok(lines.join("\n"))
+    }
+
+    // configure naming apps for the switcher, windows can be picked without
+    // a valid configure
+    fn load_picker_config() -> Option<Config> {
+        let (mut config, _) = ConfigLoader::with_default_layers(Config::schema_version())
+            .load()
+            .ok()?;
+        config.expand_paths(&PathExpander::from_env()).ok()?;
+        Some(config)
+    }
+
+    // focus the window found best by the switcher for query, recently used
+    // windows of the running instance rank higher
+    pub fn pick(query: &str) -> Result<String, AppError> {
+        let mut backend = Win32Backend;
+        let mut switcher = WindowSwitcher::new(backend.snapshot());
+        if let Some(config) = Self::load_picker_config() {
+            switcher = switcher.with_apps(&config);
+        }
+        if let Some(path) = Self::history_path() {
+            switcher = switcher.with_history(&WindowHistory::load(&path, HISTORY_CAPACITY));
+        }
+        let hits = switcher.search(query);
+        let hit = hits
+            .first()
+            .ok_or_else(|| AppError::new(format!("No Window Matches '{}'", query)))?;
+        backend.focus(hit.get_window().get_hwnd());
+        Ok(hit.get_window().to_string())
+    }
+
+    // pick a window from a list in the terminal
+    pub fn pick_tui() -> Result<(), AppError> {
+        let mut picker = WindowPicker::new().excluding(Console::get_window());
+        if let Some(config) = Self::load_picker_config() {
+            picker = picker.with_config(config);
+        }
+        if let Some(path) = Self::history_path() {
+            picker = picker.with_history(WindowHistory::load(&path, HISTORY_CAPACITY));
+        }
+        picker.run_in_terminal(&mut Win32Backend)?;
+        Ok(())
+    }
+
+    // validate configure without touching it, shows how paths are expanded on this machine
+    pub fn check() -> Result<String, AppError> {
+        let (mut config, files) =
+            ConfigLoader::with_default_layers(Config::schema_version()).load()?;
+        config.validate()?;
+        let mut summary = String::from("Configure files:\n");
+        for file in files.iter() {
+            summary.push_str(&format!("{}\n", file.get_path().display()));
+        }
+        let expanded = config.expand_paths(&PathExpander::from_env())?;
+        if !expanded.is_empty() {
+            summary.push_str("Expanded paths:\n");
+            for line in expanded {
+                summary.push_str(&line);
+                summary.push('\n');
+            }
+        }
+        Ok(summary)
+    }
+
+    pub fn migrate(dry_run: bool) -> Result<String, AppError> {
+        let (_, files) = ConfigLoader::with_default_layers(Config::schema_version()).load()?;
+        let mut summary = String::new();
+        for file in files.iter() {
+            summary.push_str(&format!("{}\n", file.get_path().display()));
+            summary.push_str(&file.get_report().summary());
+            summary.push('\n');
+        }
+        // dry run only shows what a migration would change without touching the configure file
+        if !dry_run {
+            Self::load_configure()?;
+        }
+        Ok(summary)
     }
 
     pub fn get_version(&self) -> &Version {
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utils::PathExpander;
+
+use super::{is_same_path, AppError, MatchRule};
+
+// an application defined once in `apps` and referenced by actions with `target`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AppAlias {
+    // one or more executable paths, any of them identifies the application
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub exec: Vec<String>,
+    // identifies the application by rules, like a version stamped install directory
+    #[serde(rename = "match", default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<MatchRule>,
+    // command to start the application, first exec path if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch: Option<String>,
+}
+
+impl AppAlias {
+    pub fn get_exec(&self) -> &Vec<String> {
+        &self.exec
+    }
+
+    pub fn get_launch(&self) -> Option<&String> {
+        self.launch.as_ref().or_else(|| self.exec.first())
+    }
+
+    pub fn get_rule(&self) -> Option<&MatchRule> {
+        self.rule.as_ref()
+    }
+
+    pub fn is_match(&self, process_name: &str) -> bool {
+        self.exec
+            .iter()
+            .any(|exec| is_same_path(exec, process_name))
+    }
+
+    pub fn validate(&self, name: &str) -> Result<(), AppError> {
+        if self.exec.is_empty() && self.rule.is_none() {
+            return Err(AppError::new(format!(
+                "App '{}' must have at least one exec path or 'match'",
+                name
+            )));
+        }
+        if let Some(rule) = &self.rule {
+            rule.compile()
+                .map_err(|error| AppError::new(format!("App '{}': {}", name, error)))?;
+        }
+        Ok(())
+    }
+
+    pub fn expand_paths(
+        &mut self,
+        name: &str,
+        expander: &PathExpander,
+    ) -> Result<Vec<String>, AppError> {
+        let mut expanded = Vec::new();
+        // exec paths are matched against process names, launch is a command
+        let fields = self
+            .exec
+            .iter_mut()
+            .map(|exec| ("exec", exec))
+            .chain(self.launch.iter_mut().map(|launch| ("launch", launch)));
+        for (field, path) in fields {
+            let value = match field {
+                "exec" => expander.expand_path(path),
+                _ => expander.expand(path),
+            }
+            .map_err(|error| AppError::new(format!("{}: {}", name, error)))?;
+            if &value != path {
+                expanded.push(format!("{}: {} '{}' -> '{}'", name, field, path, value));
+            }
+            *path = value;
+        }
+        Ok(expanded)
+    }
+}
+
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(exec) => Ok(vec![exec]),
+        OneOrMany::Many(exec) => Ok(exec),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::PathExpander;
+
+    use super::AppAlias;
+
+    #[test]
+    fn it_works() {
+        let alias: AppAlias = serde_json::from_str(r#"{ "exec": "C:/chrome.exe" }"#).unwrap();
+        assert_eq!(alias.get_exec(), &vec!["C:/chrome.exe".to_string()]);
+        assert_eq!(alias.get_launch().unwrap(), "C:/chrome.exe");
+        assert!(alias.is_match("C:/chrome.exe"));
+        assert!(!alias.is_match("C:/firefox.exe"));
+
+        let alias: AppAlias = serde_json::from_str(
+            r#"{ "exec": ["C:/chrome.exe", "C:/chrome_beta.exe"], "launch": "C:/launcher.exe" }"#,
+        )
+        .unwrap();
+        assert!(alias.is_match("C:/chrome_beta.exe"));
+        assert_eq!(alias.get_launch().unwrap(), "C:/launcher.exe");
+    }
+
+    #[test]
+    fn validate() {
+        let alias: AppAlias = serde_json::from_str(r#"{ "exec": [] }"#).unwrap();
+        assert!(alias.validate("browser").is_err());
+    }
+
+    #[test]
+    fn expand_paths() {
+        let mut alias: AppAlias =
+            serde_json::from_str(r#"{ "exec": ["{ProgramFiles}/app.exe", "C:/app.exe"] }"#)
+                .unwrap();
+        let expander = PathExpander::new(Box::new(|name| match name {
+            "ProgramFiles" => Some("C:/Program Files".to_string()),
+            _ => None,
+        }));
+        let expanded = alias.expand_paths("app", &expander).unwrap();
+        assert_eq!(expanded.len(), 1);
+        assert_eq!(alias.get_exec()[0], "C:/Program Files/app.exe");
+    }
+}
@@ -0,0 +1,407 @@
+use std::{fs, path::PathBuf};
+
+use windows::{
+    core::PCWSTR,
+    Win32::{
+        Foundation::{ERROR_FILE_NOT_FOUND, ERROR_SUCCESS},
+        System::Registry::{
+            RegCloseKey, RegDeleteValueW, RegOpenKeyExW, RegQueryValueExW, RegSetValueExW, HKEY,
+            HKEY_CURRENT_USER, KEY_READ, KEY_WRITE, REG_SAM_FLAGS, REG_SZ,
+        },
+    },
+};
+
+use super::{load_state_file, save_state_file, write_file, AppError};
+
+// where the login entry lives, registry on windows and xdg autostart directory on linux
+pub trait AutostartStore {
+    // command of the installed login entry
+    fn read(&self) -> Result<Option<String>, AppError>;
+    fn write(&mut self, command: &str) -> Result<(), AppError>;
+    fn remove(&mut self) -> Result<(), AppError>;
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AutostartStatus {
+    Unchanged,
+    Installed,
+    Removed,
+    // entry pointed to another command, like ektox moved to another directory
+    Updated,
+    // entry was changed outside ektox since the last run
+    Drift(String),
+}
+
+pub struct Autostart {
+    store: Box<dyn AutostartStore>,
+    // remember whether the entry was installed on last run to detect drift
+    state: Option<PathBuf>,
+}
+
+impl Autostart {
+    pub fn new(store: Box<dyn AutostartStore>, state: Option<PathBuf>) -> Self {
+        Autostart { store, state }
+    }
+
+    pub fn platform(state: Option<PathBuf>) -> Self {
+        let store: Box<dyn AutostartStore> = if cfg!(windows) {
+            Box::new(RegistryStore)
+        } else {
+            match XdgStore::from_env() {
+                Some(store) => Box::new(store),
+                None => Box::new(MemoryStore::default()),
+            }
+        };
+        Self::new(store, state)
+    }
+
+    pub fn current_command() -> Result<String, AppError> {
+        let exe = std::env::current_exe().map_err(|error| AppError::new(error.to_string()))?;
+        Ok(format!("\"{}\"", exe.display()))
+    }
+
+    pub fn sync(&mut self, enabled: bool, command: &str) -> Result<AutostartStatus, AppError> {
+        let previous = self.load_state();
+        let status = match (enabled, self.store.read()?) {
+            (true, None) => {
+                self.store.write(command)?;
+                if previous == Some(true) {
+                    AutostartStatus::Drift(
+                        "Login entry was removed outside ektox, installed again".to_string(),
+                    )
+                } else {
+                    AutostartStatus::Installed
+                }
+            }
+            (true, Some(installed)) if installed != command => {
+                self.store.write(command)?;
+                AutostartStatus::Updated
+            }
+            (false, Some(_)) => {
+                self.store.remove()?;
+                if previous == Some(false) {
+                    AutostartStatus::Drift(
+                        "Login entry was added outside ektox, removed".to_string(),
+                    )
+                } else {
+                    AutostartStatus::Removed
+                }
+            }
+            _ => AutostartStatus::Unchanged,
+        };
+        self.save_state(enabled)?;
+        Ok(status)
+    }
+
+    fn load_state(&self) -> Option<bool> {
+        load_state_file(self.state.as_ref()?)
+    }
+
+    fn save_state(&self, enabled: bool) -> Result<(), AppError> {
+        if let Some(path) = &self.state {
+            save_state_file(path, "Autostart State", &enabled)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryStore {
+    command: Option<String>,
+}
+
+impl MemoryStore {
+    pub fn new(command: Option<String>) -> Self {
+        MemoryStore { command }
+    }
+}
+
+impl AutostartStore for MemoryStore {
+    fn read(&self) -> Result<Option<String>, AppError> {
+        Ok(self.command.clone())
+    }
+
+    fn write(&mut self, command: &str) -> Result<(), AppError> {
+        self.command = Some(command.to_string());
+        Ok(())
+    }
+
+    fn remove(&mut self) -> Result<(), AppError> {
+        self.command = None;
+        Ok(())
+    }
+}
+
+// ~/.config/autostart/ektox.desktop
+pub struct XdgStore {
+    path: PathBuf,
+}
+
+impl XdgStore {
+    pub fn new(path: PathBuf) -> Self {
+        XdgStore { path }
+    }
+
+    pub fn from_env() -> Option<Self> {
+        let directory = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(directory) => PathBuf::from(directory),
+            None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        };
+        Some(Self::new(directory.join("autostart").join("ektox.desktop")))
+    }
+}
+
+impl AutostartStore for XdgStore {
+    fn read(&self) -> Result<Option<String>, AppError> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => Ok(content
+                .lines()
+                .find_map(|line| line.strip_prefix("Exec="))
+                .map(|command| command.trim().to_string())),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(AppError::new(format!(
+                "Read '{}' Failed: {}",
+                self.path.display(),
+                error
+            ))),
+        }
+    }
+
+    fn write(&mut self, command: &str) -> Result<(), AppError> {
+        let content = format!(
+            "[Desktop Entry]\nType=Application\nName=ektox\nComment={}\nExec={}\nX-GNOME-Autostart-enabled=true\n",
+            env!("CARGO_PKG_DESCRIPTION"),
+            command
+        );
+        write_file(&self.path, "Autostart Entry", &content)
+    }
+
+    fn remove(&mut self) -> Result<(), AppError> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(AppError::new(format!(
+                "Remove '{}' Failed: {}",
+                self.path.display(),
+                error
+            ))),
+        }
+    }
+}
+
+// HKCU\Software\Microsoft\Windows\CurrentVersion\Run\ektox
+pub struct RegistryStore;
+
+impl RegistryStore {
+    const RUN_KEY: &'static str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+    const VALUE_NAME: &'static str = "ektox";
+
+    fn open(access: REG_SAM_FLAGS) -> Result<HKEY, AppError> {
+        let mut key = HKEY::default();
+        let sub_key = wide(Self::RUN_KEY);
+        let result = unsafe {
+            RegOpenKeyExW(
+                HKEY_CURRENT_USER,
+                PCWSTR(sub_key.as_ptr()),
+                0,
+                access,
+                &mut key,
+            )
+        };
+        if result != ERROR_SUCCESS {
+            return Err(AppError::new(format!(
+                "Open Registry Key '{}' Failed: {}",
+                Self::RUN_KEY,
+                result.0
+            )));
+        }
+        Ok(key)
+    }
+}
+
+impl AutostartStore for RegistryStore {
+    fn read(&self) -> Result<Option<String>, AppError> {
+        let key = Self::open(KEY_READ)?;
+        let value_name = wide(Self::VALUE_NAME);
+        let mut size: u32 = 0;
+        let mut buffer: Vec<u16> = Vec::new();
+        let mut result = unsafe {
+            RegQueryValueExW(
+                key,
+                PCWSTR(value_name.as_ptr()),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                &mut size,
+            )
+        };
+        if result == ERROR_SUCCESS {
+            buffer = vec![0; (size as usize).div_ceil(2)];
+            result = unsafe {
+                RegQueryValueExW(
+                    key,
+                    PCWSTR(value_name.as_ptr()),
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    buffer.as_mut_ptr() as *mut u8,
+                    &mut size,
+                )
+            };
+        }
+        unsafe {
+            RegCloseKey(key);
+        }
+        match result {
+            ERROR_SUCCESS => {
+                let len = buffer.iter().position(|c| *c == 0).unwrap_or(buffer.len());
+                Ok(Some(String::from_utf16_lossy(&buffer[0..len])))
+            }
+            ERROR_FILE_NOT_FOUND => Ok(None),
+            _ => Err(AppError::new(format!(
+                "Read Registry Value '{}' Failed: {}",
+                Self::VALUE_NAME,
+                result.0
+            ))),
+        }
+    }
+
+    fn write(&mut self, command: &str) -> Result<(), AppError> {
+        let key = Self::open(KEY_WRITE)?;
+        let value_name = wide(Self::VALUE_NAME);
+        let data = wide(command);
+        let result = unsafe {
+            let result = RegSetValueExW(
+                key,
+                PCWSTR(value_name.as_ptr()),
+                0,
+                REG_SZ,
+                data.as_ptr() as *const u8,
+                (data.len() * 2) as u32,
+            );
+            RegCloseKey(key);
+            result
+        };
+        if result != ERROR_SUCCESS {
+            return Err(AppError::new(format!(
+                "Write Registry Value '{}' Failed: {}",
+                Self::VALUE_NAME,
+                result.0
+            )));
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self) -> Result<(), AppError> {
+        let key = Self::open(KEY_WRITE)?;
+        let value_name = wide(Self::VALUE_NAME);
+        let result = unsafe {
+            let result = RegDeleteValueW(key, PCWSTR(value_name.as_ptr()));
+            RegCloseKey(key);
+            result
+        };
+        if result != ERROR_SUCCESS && result != ERROR_FILE_NOT_FOUND {
+            return Err(AppError::new(format!(
+                "Remove Registry Value '{}' Failed: {}",
+                Self::VALUE_NAME,
+                result.0
+            )));
+        }
+        Ok(())
+    }
+}
+
+// null terminated utf-16 string for wide win32 apis
+fn wide(str: &str) -> Vec<u16> {
+    str.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::{Autostart, AutostartStatus, AutostartStore, MemoryStore, XdgStore};
+
+    fn temp_directory(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("ektox-autostart-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn it_works() {
+        let mut autostart = Autostart::new(Box::new(MemoryStore::default()), None);
+        assert_eq!(
+            autostart.sync(true, "ektox").unwrap(),
+            AutostartStatus::Installed
+        );
+        assert_eq!(
+            autostart.sync(true, "ektox").unwrap(),
+            AutostartStatus::Unchanged
+        );
+        assert_eq!(
+            autostart.sync(true, "/opt/ektox").unwrap(),
+            AutostartStatus::Updated
+        );
+        assert_eq!(
+            autostart.sync(false, "/opt/ektox").unwrap(),
+            AutostartStatus::Removed
+        );
+        assert_eq!(
+            autostart.sync(false, "/opt/ektox").unwrap(),
+            AutostartStatus::Unchanged
+        );
+    }
+
+    #[test]
+    fn drift() {
+        let directory = temp_directory("drift");
+        let state = directory.join("autostart.state");
+
+        let mut autostart = Autostart::new(Box::new(MemoryStore::default()), Some(state.clone()));
+        assert_eq!(
+            autostart.sync(true, "ektox").unwrap(),
+            AutostartStatus::Installed
+        );
+
+        // entry removed manually while ektox was not running
+        let mut autostart = Autostart::new(Box::new(MemoryStore::new(None)), Some(state.clone()));
+        assert!(matches!(
+            autostart.sync(true, "ektox").unwrap(),
+            AutostartStatus::Drift(_)
+        ));
+
+        assert_eq!(
+            autostart.sync(false, "ektox").unwrap(),
+            AutostartStatus::Removed
+        );
+        let mut autostart = Autostart::new(
+            Box::new(MemoryStore::new(Some("ektox".to_string()))),
+            Some(state),
+        );
+        assert!(matches!(
+            autostart.sync(false, "ektox").unwrap(),
+            AutostartStatus::Drift(_)
+        ));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn xdg() {
+        let directory = temp_directory("xdg");
+        let path = directory.join("autostart").join("ektox.desktop");
+        let mut store = XdgStore::new(path.clone());
+        assert_eq!(store.read().unwrap(), None);
+        store.write("/usr/bin/ektox").unwrap();
+        assert_eq!(store.read().unwrap().as_deref(), Some("/usr/bin/ektox"));
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .starts_with("[Desktop Entry]"));
+        store.remove().unwrap();
+        assert_eq!(store.read().unwrap(), None);
+        store.remove().unwrap();
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -1,27 +1,298 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
-use crate::utils::Hotkey;
+use crate::utils::PathExpander;
+
+use super::{
+    Action, ActionKind, AppAlias, AppError, Eligibility, NumberedBindings, Target, Version,
+    WindowMatcher,
+};
+
+// shape of configure files this ektox reads and writes, raised by a migration
+// only, not by every release of ektox
+pub const CONFIG_SCHEMA_VERSION: &str = "1.2.0";
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Config {
+    // configure file without version was written before versioning was introduced
+    #[serde(default = "Config::legacy_version")]
+    pub version: Version,
     pub startup: bool,
+    // applications referenced by name from `target` of actions
+    #[serde(default)]
+    pub apps: BTreeMap<String, AppAlias>,
+    // actions of the base profile, every profile inherits them unless it extends another profile
     pub actions: Vec<Action>,
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+    // profile activated on startup, base profile if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    // which windows actions can reach
+    #[serde(default, skip_serializing_if = "Eligibility::is_empty")]
+    pub eligibility: Eligibility,
+    // how many focused windows `back` and `forward` walk through
+    #[serde(
+        default = "Config::default_navigation_depth",
+        skip_serializing_if = "Config::is_default_navigation_depth"
+    )]
+    pub navigation_depth: usize,
+    // keep marked windows across restarts, found again by their app and title
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub persist_marks: bool,
+    // hotkeys focusing windows by number, like the taskbar
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub numbered: Option<NumberedBindings>,
 }
 
 impl Config {
+    pub fn legacy_version() -> Version {
+        Version::new("1.0.0")
+    }
+
+    pub fn schema_version() -> Version {
+        Version::new(CONFIG_SCHEMA_VERSION)
+    }
+
+    pub fn default_navigation_depth() -> usize {
+        16
+    }
+
+    fn is_default_navigation_depth(depth: &usize) -> bool {
+        *depth == Self::default_navigation_depth()
+    }
+
+    pub fn get_version(&self) -> &Version {
+        &self.version
+    }
+
     pub fn get_actions(&self) -> &Vec<Action> {
         &self.actions
     }
+
+    pub fn get_apps(&self) -> &BTreeMap<String, AppAlias> {
+        &self.apps
+    }
+
+    pub fn get_app(&self, name: &str) -> Option<&AppAlias> {
+        self.apps.get(name)
+    }
+
+    // executable paths identifying the windows of target
+    pub fn get_exec_paths(&self, target: &Target) -> Vec<String> {
+        if let Some(exec) = &target.exec {
+            return vec![exec.clone()];
+        }
+        match target.target.as_ref().and_then(|name| self.get_app(name)) {
+            Some(app) => app.get_exec().clone(),
+            None => Vec::new(),
+        }
+    }
+
+    // rules identifying the windows of target, `match` and `query` take precedence over `exec`
+    pub fn get_matcher(&self, target: &Target) -> Result<WindowMatcher, AppError> {
+        let mut matcher = WindowMatcher::new();
+        if let Some(rule) = &target.rule {
+            matcher.add_rule(rule)?;
+            return Ok(matcher);
+        }
+        if let Some(query) = &target.query {
+            matcher.add_query(query)?;
+            return Ok(matcher);
+        }
+        for path in self.get_exec_paths(target) {
+            matcher.add_path(&path);
+        }
+        let app = target.target.as_ref().and_then(|name| self.get_app(name));
+        if let Some(rule) = app.and_then(|app| app.get_rule()) {
+            matcher.add_rule(rule)?;
+        }
+        Ok(matcher)
+    }
+
+    pub fn get_profiles(&self) -> &Vec<Profile> {
+        &self.profiles
+    }
+
+    pub fn get_profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    pub fn get_default_profile(&self) -> Option<&String> {
+        self.profile.as_ref()
+    }
+
+    // pinned entry of numbered bindings, an app defined in `apps` or an executable path
+    pub fn get_pinned_target(&self, pinned: &str) -> Target {
+        match self.get_app(pinned) {
+            Some(_) => Target::app(pinned),
+            None => Target::exec(pinned),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), AppError> {
+        if let Some(name) = &self.profile {
+            self.resolve_actions(Some(name))?;
+        }
+        if let Some(numbered) = &self.numbered {
+            numbered.get_actions()?;
+        }
+        for (name, app) in self.apps.iter() {
+            app.validate(name)?;
+        }
+        for profile in self.profiles.iter() {
+            if self
+                .profiles
+                .iter()
+                .filter(|p| p.name == profile.name)
+                .count()
+                > 1
+            {
+                return Err(AppError::new(format!(
+                    "Profile '{}' is defined more than once",
+                    profile.name
+                )));
+            }
+            self.resolve_actions(Some(&profile.name))?;
+        }
+        let actions = self.actions.iter().chain(
+            self.profiles
+                .iter()
+                .flat_map(|profile| profile.actions.iter()),
+        );
+        for action in actions {
+            action.validate()?;
+            if let ActionKind::SwitchProfile {
+                profile: Some(name),
+            } = &action.kind
+            {
+                if self.get_profile(name).is_none() {
+                    return Err(AppError::new(format!(
+                        "Action '{}' switch to unknown profile '{}'",
+                        action.hotkey, name
+                    )));
+                }
+            }
+            if let Some(name) = action.kind.get_target().and_then(|t| t.target.as_ref()) {
+                if self.get_app(name).is_none() {
+                    return Err(AppError::new(format!(
+                        "Action '{}' target unknown app '{}'",
+                        action.hotkey, name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // expand variables of path-like fields in place, returns how each changed path was expanded
+    pub fn expand_paths(&mut self, expander: &PathExpander) -> Result<Vec<String>, AppError> {
+        let mut expanded = Vec::new();
+        for (name, app) in self.apps.iter_mut() {
+            expanded.append(&mut app.expand_paths(name, expander)?);
+        }
+        let actions = self.actions.iter_mut().chain(
+            self.profiles
+                .iter_mut()
+                .flat_map(|profile| profile.actions.iter_mut()),
+        );
+        if let Some(numbered) = self.numbered.as_mut() {
+            let apps = &self.apps;
+            for pinned in numbered.get_pinned_mut() {
+                if apps.contains_key(pinned) {
+                    continue;
+                }
+                let value = expander
+                    .expand_path(pinned)
+                    .map_err(|error| AppError::new(format!("numbered: {}", error)))?;
+                if &value != pinned {
+                    expanded.push(format!("numbered: pinned '{}' -> '{}'", pinned, value));
+                }
+                *pinned = value;
+            }
+        }
+        for action in actions {
+            let hotkey = action.hotkey.to_string();
+            for (field, path) in action.get_paths_mut() {
+                // exec and cwd are matched as paths, the others are passed on as written
+                let value = match field {
+                    "exec" | "cwd" => expander.expand_path(path),
+                    _ => expander.expand(path),
+                }
+                .map_err(|error| AppError::new(format!("{}: {}", hotkey, error)))?;
+                if &value != path {
+                    expanded.push(format!("{}: {} '{}' -> '{}'", hotkey, field, path, value));
+                }
+                *path = value;
+            }
+        }
+        Ok(expanded)
+    }
+
+    // collect actions of a profile with its ancestors, actions of a child profile
+    // replace the inherited actions bound to the same hotkey
+    pub fn resolve_actions(&self, profile: Option<&String>) -> Result<Vec<Action>, AppError> {
+        let mut chain: Vec<&Profile> = Vec::new();
+        let mut next = profile;
+        while let Some(name) = next {
+            let profile = match self.get_profile(name) {
+                Some(profile) => profile,
+                None => return Err(AppError::new(format!("Profile '{}' Not Found", name))),
+            };
+            if chain.iter().any(|p| p.name == profile.name) {
+                return Err(AppError::new(format!(
+                    "Profile '{}' inherits from itself",
+                    profile.name
+                )));
+            }
+            chain.push(profile);
+            next = profile.extends.as_ref();
+        }
+
+        let mut actions: Vec<Action> = self.actions.clone();
+        for profile in chain.into_iter().rev() {
+            for action in profile.actions.iter() {
+                actions.retain(|inherited| inherited.hotkey != action.hotkey);
+                actions.push(action.clone());
+            }
+        }
+        // written actions take precedence over generated ones
+        if let Some(numbered) = &self.numbered {
+            for action in numbered.get_actions()? {
+                if !actions
+                    .iter()
+                    .any(|written| written.hotkey == action.hotkey)
+                {
+                    actions.push(action);
+                }
+            }
+        }
+        Ok(actions)
+    }
 }
+
 #[derive(Serialize, Deserialize, Debug)]
-pub struct Action {
-    pub hotkey: Hotkey,
-    pub exec: String,
+pub struct Profile {
+    pub name: String,
+    // parent profile, inherits the base profile if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+}
+
+impl Profile {
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Config;
+    use crate::utils::PathExpander;
+
+    use super::{ActionKind, Config, Version};
 
     #[test]
     fn it_works() {
@@ -31,16 +302,208 @@ mod tests {
             "actions": [
               {
                 "hotkey": "ctrl + 1",
+                "kind": "focus",
                 "exec": "C:/ProgramFile/test.exe"
               },
               {
                 "hotkey": "ctrl + 2",
+                "kind": "focus",
                 "exec": "C:/ProgramFile/test.exe"
               }
             ]
         }"#;
         let config: Config = serde_json::from_str(data).unwrap();
         println!("result = {:#?}", config);
+        assert_eq!(config.get_version(), &Config::legacy_version());
+    }
+
+    #[test]
+    fn profiles() {
+        let data = r#"
+        {
+            "startup": true,
+            "actions": [
+              { "hotkey": "ctrl + 1", "kind": "focus", "exec": "C:/base1.exe" },
+              { "hotkey": "ctrl + 2", "kind": "focus", "exec": "C:/base2.exe" },
+              { "hotkey": "ctrl + g", "kind": "switch_profile", "profile": "gaming" }
+            ],
+            "profiles": [
+              {
+                "name": "gaming",
+                "actions": [
+                  { "hotkey": "ctrl + 2", "kind": "focus", "exec": "C:/game.exe" },
+                  { "hotkey": "ctrl + g", "kind": "switch_profile", "profile": "streaming" }
+                ]
+              },
+              {
+                "name": "streaming",
+                "extends": "gaming",
+                "actions": [{ "hotkey": "ctrl + 3", "kind": "focus", "exec": "C:/obs.exe" }]
+              }
+            ]
+        }"#;
+        let config: Config = serde_json::from_str(data).unwrap();
+        config.validate().unwrap();
+
+        let base = config.resolve_actions(None).unwrap();
+        assert_eq!(base.len(), 3);
+
+        let streaming = config
+            .resolve_actions(Some(&"streaming".to_string()))
+            .unwrap();
+        let execs: Vec<Option<&str>> = streaming
+            .iter()
+            .map(|a| a.kind.get_target().and_then(|t| t.exec.as_deref()))
+            .collect();
+        assert_eq!(
+            execs,
+            vec![
+                Some("C:/base1.exe"),
+                Some("C:/game.exe"),
+                None,
+                Some("C:/obs.exe")
+            ]
+        );
+        assert!(matches!(
+            &streaming[2].kind,
+            ActionKind::SwitchProfile { profile: Some(profile) } if profile == "streaming"
+        ));
+
+        assert!(config.resolve_actions(Some(&"work".to_string())).is_err());
+    }
+
+    #[test]
+    fn invalid_profiles() {
+        let data = r#"
+        {
+            "startup": true,
+            "actions": [],
+            "profiles": [
+              { "name": "a", "extends": "b" },
+              { "name": "b", "extends": "a" }
+            ]
+        }"#;
+        let config: Config = serde_json::from_str(data).unwrap();
+        assert!(config.validate().is_err());
+
+        let data = r#"
+        {
+            "startup": true,
+            "actions": [{ "hotkey": "ctrl + 1", "kind": "switch_profile", "profile": "missing" }]
+        }"#;
+        let config: Config = serde_json::from_str(data).unwrap();
+        assert!(config.validate().is_err());
+
+        let data = r#"
+        {
+            "startup": true,
+            "actions": [{ "hotkey": "ctrl + 1", "kind": "focus" }]
+        }"#;
+        let config: Config = serde_json::from_str(data).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn expand_paths() {
+        let data = r#"
+        {
+            "startup": true,
+            "actions": [
+              { "hotkey": "ctrl + 1", "kind": "focus", "exec": "%LOCALAPPDATA%/Discord/Update.exe" },
+              { "hotkey": "ctrl + 2", "kind": "focus", "exec": "C:/ProgramFile/test.exe" },
+              { "hotkey": "ctrl + 4", "kind": "launch", "exec": "C:/code.exe", "args": ["%LOCALAPPDATA%\\notes", "-n"] }
+            ],
+            "profiles": [
+              { "name": "work", "actions": [{ "hotkey": "ctrl + 3", "kind": "focus", "exec": "~/bin/app.exe" }] }
+            ]
+        }"#;
+        let mut config: Config = serde_json::from_str(data).unwrap();
+        let expander = PathExpander::new(Box::new(|name| match name {
+            "LOCALAPPDATA" => Some("C:\\Users\\alice\\AppData\\Local".to_string()),
+            "USERPROFILE" => Some("C:/Users/alice".to_string()),
+            _ => None,
+        }));
+        let expanded = config.expand_paths(&expander).unwrap();
+        assert_eq!(expanded.len(), 3);
+        assert_eq!(
+            config.actions[0].kind.get_target().unwrap().exec.as_deref(),
+            Some("C:/Users/alice/AppData/Local/Discord/Update.exe")
+        );
+        assert_eq!(
+            config.profiles[0].actions[0]
+                .kind
+                .get_target()
+                .unwrap()
+                .exec
+                .as_deref(),
+            Some("C:/Users/alice/bin/app.exe")
+        );
+        // arguments are expanded as written
+        match &config.actions[2].kind {
+            ActionKind::Launch { launch, .. } => assert_eq!(
+                launch.args,
+                vec!["C:\\Users\\alice\\AppData\\Local\\notes", "-n"]
+            ),
+            kind => panic!("unexpected {:?}", kind),
+        }
+
+        let mut config: Config = serde_json::from_str(data).unwrap();
+        let expander = PathExpander::new(Box::new(|_| None));
+        assert!(config.expand_paths(&expander).is_err());
+    }
+
+    #[test]
+    fn apps() {
+        let data = r#"
+        {
+            "startup": true,
+            "apps": {
+                "browser": { "exec": ["C:/chrome.exe", "C:/chrome_beta.exe"] }
+            },
+            "actions": [
+              { "hotkey": "ctrl + 1", "kind": "focus", "target": "browser" },
+              { "hotkey": "ctrl + 2", "kind": "focus", "exec": "C:/code.exe" }
+            ]
+        }"#;
+        let config: Config = serde_json::from_str(data).unwrap();
+        config.validate().unwrap();
+        assert_eq!(
+            config.get_exec_paths(config.actions[0].kind.get_target().unwrap()),
+            vec!["C:/chrome.exe", "C:/chrome_beta.exe"]
+        );
+        assert_eq!(
+            config.get_exec_paths(config.actions[1].kind.get_target().unwrap()),
+            vec!["C:/code.exe"]
+        );
+
+        let data = r#"
+        {
+            "startup": true,
+            "actions": [{ "hotkey": "ctrl + 1", "kind": "focus", "target": "browser" }]
+        }"#;
+        let config: Config = serde_json::from_str(data).unwrap();
+        assert!(config.validate().is_err());
+
+        let data = r#"
+        {
+            "startup": true,
+            "apps": { "browser": { "exec": "C:/chrome.exe" } },
+            "actions": [{ "hotkey": "ctrl + 1", "kind": "focus", "target": "browser", "exec": "C:/chrome.exe" }]
+        }"#;
+        let config: Config = serde_json::from_str(data).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn version() {
+        let data = r#"
+        {
+            "version": "1.1.0",
+            "startup": true,
+            "actions": []
+        }"#;
+        let config: Config = serde_json::from_str(data).unwrap();
+        assert_eq!(config.get_version(), &Version::new("1.1.0"));
     }
     #[test]
 
@@ -51,16 +514,18 @@ mod tests {
             "actions": [
               {
                 "hotkey": "ctrl + 1 + delete",
+                "kind": "focus",
                 "exec": "C:/ProgramFile/test.exe"
               },
               {
                 "hotkey": "ctrl + 2",
+                "kind": "focus",
                 "exec": "C:/ProgramFile/test.exe"
               }
             ]
         }"#;
         let parse_result = serde_json::from_str::<Config>(data);
-        if let Ok(_) = parse_result {
+        if parse_result.is_ok() {
             panic!("should return error");
         }
     }
@@ -0,0 +1,531 @@
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use serde_json::{Map, Value};
+
+use super::{AppError, Config, ConfigMigrator, MigrationReport, Version};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerKind {
+    // machine-wide defaults shipped by administrators
+    System,
+    // the configure file of current user
+    User,
+    // optional override in working directory
+    Local,
+    // file pulled in by `include` of another file
+    Include,
+}
+
+pub struct ConfigLayer {
+    kind: LayerKind,
+    path: PathBuf,
+}
+
+impl ConfigLayer {
+    pub fn new(kind: LayerKind, path: PathBuf) -> Self {
+        ConfigLayer { kind, path }
+    }
+
+    pub fn get_kind(&self) -> LayerKind {
+        self.kind
+    }
+
+    pub fn get_path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn system() -> Option<Self> {
+        let directory = if cfg!(windows) {
+            PathBuf::from(std::env::var_os("ProgramData")?)
+        } else {
+            PathBuf::from("/etc")
+        };
+        Some(Self::new(
+            LayerKind::System,
+            directory.join("ektox").join("config.json"),
+        ))
+    }
+
+    pub fn user() -> Option<Self> {
+        let directory = if cfg!(windows) {
+            PathBuf::from(std::env::var_os("APPDATA")?)
+        } else {
+            match std::env::var_os("XDG_CONFIG_HOME") {
+                Some(directory) => PathBuf::from(directory),
+                None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+            }
+        };
+        Some(Self::new(
+            LayerKind::User,
+            directory.join("ektox").join("config.json"),
+        ))
+    }
+
+    pub fn local() -> Self {
+        Self::new(LayerKind::Local, PathBuf::from("./config.json"))
+    }
+}
+
+pub struct LoadedFile {
+    kind: LayerKind,
+    path: PathBuf,
+    report: MigrationReport,
+}
+
+impl LoadedFile {
+    pub fn get_kind(&self) -> LayerKind {
+        self.kind
+    }
+
+    pub fn get_path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn get_report(&self) -> &MigrationReport {
+        &self.report
+    }
+
+    // system and included files may be shared and read-only, they are only migrated in memory
+    pub fn is_writable(&self) -> bool {
+        matches!(self.kind, LayerKind::User | LayerKind::Local)
+    }
+}
+
+pub struct ConfigLoader {
+    version: Version,
+    layers: Vec<ConfigLayer>,
+}
+
+impl ConfigLoader {
+    pub fn new(version: Version) -> Self {
+        ConfigLoader {
+            version,
+            layers: Vec::new(),
+        }
+    }
+
+    // system defaults, then user file, then local override
+    pub fn with_default_layers(version: Version) -> Self {
+        let mut loader = Self::new(version);
+        if let Some(layer) = ConfigLayer::system() {
+            loader.add_layer(layer);
+        }
+        if let Some(layer) = ConfigLayer::user() {
+            loader.add_layer(layer);
+        }
+        loader.add_layer(ConfigLayer::local());
+        loader
+    }
+
+    pub fn add_layer(&mut self, layer: ConfigLayer) {
+        self.layers.push(layer);
+    }
+
+    pub fn get_layers(&self) -> &Vec<ConfigLayer> {
+        &self.layers
+    }
+
+    pub fn load(&self) -> Result<(Config, Vec<LoadedFile>), AppError> {
+        let mut merged = Map::new();
+        let mut files = Vec::new();
+        for layer in self.layers.iter() {
+            if !layer.path.exists() {
+                continue;
+            }
+            let mut stack = Vec::new();
+            let value = self.load_file(layer.kind, &layer.path, &mut stack, &mut files)?;
+            merge_config(&mut merged, value);
+        }
+        if files.is_empty() {
+            return Err(AppError::new("Configure File Not Found".to_string()));
+        }
+        merged.insert(
+            "version".to_string(),
+            Value::String(self.version.to_string()),
+        );
+        let config = serde_json::from_value::<Config>(Value::Object(merged))
+            .map_err(|error| AppError::new(error.to_string()))?;
+        Ok((config, files))
+    }
+
+    // read, migrate and resolve includes of one file, included files are merged
+    // first so that the including file overrides them
+    fn load_file(
+        &self,
+        kind: LayerKind,
+        path: &Path,
+        stack: &mut Vec<PathBuf>,
+        files: &mut Vec<LoadedFile>,
+    ) -> Result<Map<String, Value>, AppError> {
+        let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if stack.contains(&canonical) {
+            return Err(AppError::new(format!(
+                "Configure File '{}' includes itself",
+                path.display()
+            )));
+        }
+        stack.push(canonical);
+
+        let source = read_file(path)?;
+        let report = ConfigMigrator::new(self.version.clone())
+            .migrate(&source)
+            .map_err(|error| AppError::new(format!("{}: {}", path.display(), error)))?;
+        let mut value = match report.get_migrated() {
+            Value::Object(object) => object.clone(),
+            _ => Map::new(),
+        };
+        files.push(LoadedFile {
+            kind,
+            path: path.to_path_buf(),
+            report,
+        });
+
+        let mut merged = Map::new();
+        if let Some(include) = value.remove("include") {
+            let include = match include {
+                Value::String(path) => vec![Value::String(path)],
+                Value::Array(paths) => paths,
+                _ => {
+                    return Err(AppError::new(format!(
+                        "{}: 'include' must be a path or a list of paths",
+                        path.display()
+                    )))
+                }
+            };
+            let directory = path.parent().unwrap_or_else(|| Path::new("."));
+            for included in include.iter() {
+                let included = match included {
+                    Value::String(included) => directory.join(included),
+                    _ => {
+                        return Err(AppError::new(format!(
+                            "{}: 'include' must be a path or a list of paths",
+                            path.display()
+                        )))
+                    }
+                };
+                let included = self.load_file(LayerKind::Include, &included, stack, files)?;
+                merge_config(&mut merged, included);
+            }
+        }
+        // removal applies to includes and to the lower layers as well
+        let remove = value.get("remove").cloned();
+        merge_config(&mut merged, value);
+        if let Some(remove) = remove {
+            merged.insert("remove".to_string(), remove);
+        }
+
+        stack.pop();
+        Ok(merged)
+    }
+}
+
+fn read_file(path: &Path) -> Result<Value, AppError> {
+    match File::open(path) {
+        Ok(file) => match serde_json::from_reader::<File, Value>(file) {
+            Ok(source) => Ok(source),
+            Err(error) => Err(AppError::new(format!("{}: {}", path.display(), error))),
+        },
+        Err(io_error) => {
+            let msg = match io_error.kind() {
+                std::io::ErrorKind::NotFound => "Configure File Not Found",
+                std::io::ErrorKind::PermissionDenied => " Open Configure File Permision Denied",
+                _ => "Unexpected error",
+            };
+            Err(AppError::new(format!("{}: {}", msg, path.display())))
+        }
+    }
+}
+
+// merge an upper layer into lower layers
+// scalar fields: replaced by upper layer
+// actions: appended, an action with the same id as a lower one replaces it in place
+// remove: ids of lower actions to drop, before actions of the upper layer are
+// added, so that an action can be removed and defined again
+// profiles: merged by name with the same rules for their actions
+// apps: replaced by name
+pub fn merge_config(lower: &mut Map<String, Value>, upper: Map<String, Value>) {
+    let remove = match upper.get("remove") {
+        Some(Value::Array(ids)) => ids.clone(),
+        _ => Vec::new(),
+    };
+    remove_actions(lower, &remove);
+    if let Some(Value::Array(profiles)) = lower.get_mut("profiles") {
+        for profile in profiles.iter_mut() {
+            if let Value::Object(profile) = profile {
+                remove_actions(profile, &remove);
+            }
+        }
+    }
+    for (key, value) in upper.into_iter() {
+        match key.as_str() {
+            "remove" => {}
+            "actions" => merge_actions(lower, value),
+            "profiles" => merge_profiles(lower, value),
+            "apps" => merge_apps(lower, value),
+            _ => {
+                lower.insert(key, value);
+            }
+        }
+    }
+}
+
+fn merge_actions(lower: &mut Map<String, Value>, upper: Value) {
+    let upper = match upper {
+        Value::Array(actions) => actions,
+        other => {
+            // let deserialization report the malformed field
+            lower.insert("actions".to_string(), other);
+            return;
+        }
+    };
+    let actions = lower
+        .entry("actions".to_string())
+        .or_insert_with(|| Value::Array(Vec::new()));
+    if !actions.is_array() {
+        *actions = Value::Array(Vec::new());
+    }
+    if let Value::Array(actions) = actions {
+        for action in upper.into_iter() {
+            let existing = action
+                .get("id")
+                .and_then(|id| actions.iter().position(|a| a.get("id") == Some(id)));
+            match existing {
+                Some(index) => actions[index] = action,
+                None => actions.push(action),
+            }
+        }
+    }
+}
+
+fn merge_profiles(lower: &mut Map<String, Value>, upper: Value) {
+    let upper = match upper {
+        Value::Array(profiles) => profiles,
+        other => {
+            lower.insert("profiles".to_string(), other);
+            return;
+        }
+    };
+    let profiles = lower
+        .entry("profiles".to_string())
+        .or_insert_with(|| Value::Array(Vec::new()));
+    if !profiles.is_array() {
+        *profiles = Value::Array(Vec::new());
+    }
+    if let Value::Array(profiles) = profiles {
+        for profile in upper.into_iter() {
+            let existing = profile
+                .get("name")
+                .and_then(|name| profiles.iter().position(|p| p.get("name") == Some(name)));
+            match (existing, profile) {
+                (Some(index), Value::Object(profile)) => {
+                    if let Value::Object(lower) = &mut profiles[index] {
+                        merge_config(lower, profile);
+                    }
+                }
+                (_, profile) => profiles.push(profile),
+            }
+        }
+    }
+}
+
+// apps are replaced by name, other apps of lower layers are kept
+fn merge_apps(lower: &mut Map<String, Value>, upper: Value) {
+    match (lower.get_mut("apps"), upper) {
+        (Some(Value::Object(apps)), Value::Object(upper)) => {
+            for (name, app) in upper.into_iter() {
+                apps.insert(name, app);
+            }
+        }
+        (_, upper) => {
+            lower.insert("apps".to_string(), upper);
+        }
+    }
+}
+
+fn remove_actions(object: &mut Map<String, Value>, ids: &[Value]) {
+    if ids.is_empty() {
+        return;
+    }
+    if let Some(Value::Array(actions)) = object.get_mut("actions") {
+        actions.retain(|action| match action.get("id") {
+            Some(id) => !ids.contains(id),
+            None => true,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use serde_json::{json, Value};
+
+    use super::{merge_config, ConfigLayer, ConfigLoader, LayerKind, Version};
+
+    fn temp_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "ektox-config-loader-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn execs(value: &Value) -> Vec<&str> {
+        value["actions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|action| action["exec"].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn merge() {
+        let mut lower = json!({
+            "startup": false,
+            "apps": {
+                "browser": { "exec": "C:/chrome.exe" },
+                "mail": { "exec": "C:/outlook.exe" }
+            },
+            "actions": [
+                { "id": "browser", "hotkey": "ctrl + 1", "exec": "C:/chrome.exe" },
+                { "id": "mail", "hotkey": "ctrl + 2", "exec": "C:/outlook.exe" },
+                { "hotkey": "ctrl + 3", "exec": "C:/notepad.exe" }
+            ],
+            "profiles": [
+                { "name": "work", "actions": [{ "id": "chat", "hotkey": "ctrl + 4", "exec": "C:/teams.exe" }] }
+            ]
+        });
+        let upper = json!({
+            "startup": true,
+            "remove": ["mail", "chat"],
+            "apps": { "browser": { "exec": "C:/firefox.exe" } },
+            "actions": [
+                { "id": "browser", "hotkey": "ctrl + 1", "exec": "C:/firefox.exe" },
+                { "hotkey": "ctrl + 5", "exec": "C:/code.exe" }
+            ],
+            "profiles": [
+                { "name": "work", "actions": [{ "hotkey": "ctrl + 6", "exec": "C:/slack.exe" }] },
+                { "name": "gaming" }
+            ]
+        });
+        merge_config(
+            lower.as_object_mut().unwrap(),
+            upper.as_object().unwrap().clone(),
+        );
+        assert_eq!(lower["startup"], json!(true));
+        assert_eq!(lower["apps"]["browser"]["exec"], json!("C:/firefox.exe"));
+        assert_eq!(lower["apps"]["mail"]["exec"], json!("C:/outlook.exe"));
+        assert_eq!(
+            execs(&lower),
+            vec!["C:/firefox.exe", "C:/notepad.exe", "C:/code.exe"]
+        );
+        assert_eq!(execs(&lower["profiles"][0]), vec!["C:/slack.exe"]);
+        assert_eq!(lower["profiles"][1]["name"], json!("gaming"));
+        assert!(lower.get("remove").is_none());
+
+        // removed and defined again, the upper action is kept
+        let upper = json!({
+            "remove": ["browser"],
+            "actions": [{ "id": "browser", "hotkey": "ctrl + 9", "exec": "C:/edge.exe" }]
+        });
+        merge_config(
+            lower.as_object_mut().unwrap(),
+            upper.as_object().unwrap().clone(),
+        );
+        assert_eq!(
+            execs(&lower),
+            vec!["C:/notepad.exe", "C:/code.exe", "C:/edge.exe"]
+        );
+    }
+
+    #[test]
+    fn it_works() {
+        let directory = temp_directory("layers");
+        fs::write(
+            directory.join("system.json"),
+            r#"{
+                "startup": false,
+                "include": "baseline.json",
+                "actions": [{ "id": "mail", "hotkey": "ctrl + 2", "exec": "C:/outlook.exe" }]
+            }"#,
+        )
+        .unwrap();
+        fs::write(
+            directory.join("baseline.json"),
+            r#"{ "actions": [{ "id": "browser", "hotkey": "ctrl + 1", "exec": "C:/chrome.exe" }] }"#,
+        )
+        .unwrap();
+        fs::write(
+            directory.join("user.json"),
+            r#"{
+                "version": "1.2.0",
+                "startup": true,
+                "remove": ["mail"],
+                "actions": [{ "hotkey": "ctrl + 3", "kind": "focus", "exec": "C:/code.exe" }]
+            }"#,
+        )
+        .unwrap();
+
+        let mut loader = ConfigLoader::new(Version::new("1.2.0"));
+        loader.add_layer(ConfigLayer::new(
+            LayerKind::System,
+            directory.join("system.json"),
+        ));
+        loader.add_layer(ConfigLayer::new(
+            LayerKind::User,
+            directory.join("user.json"),
+        ));
+        loader.add_layer(ConfigLayer::new(
+            LayerKind::Local,
+            directory.join("missing.json"),
+        ));
+        let (config, files) = loader.load().unwrap();
+        assert!(config.startup);
+        let execs: Vec<&str> = config
+            .get_actions()
+            .iter()
+            .map(|action| action.kind.get_target().unwrap().exec.as_deref().unwrap())
+            .collect();
+        assert_eq!(execs, vec!["C:/chrome.exe", "C:/code.exe"]);
+
+        let kinds: Vec<LayerKind> = files.iter().map(|file| file.get_kind()).collect();
+        assert_eq!(
+            kinds,
+            vec![LayerKind::System, LayerKind::Include, LayerKind::User]
+        );
+        assert!(files[0].get_report().is_required());
+        assert!(!files[2].get_report().is_required());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn include_cycle() {
+        let directory = temp_directory("cycle");
+        fs::write(directory.join("a.json"), r#"{ "include": ["b.json"] }"#).unwrap();
+        fs::write(directory.join("b.json"), r#"{ "include": ["a.json"] }"#).unwrap();
+        let mut loader = ConfigLoader::new(Version::new("1.1.0"));
+        loader.add_layer(ConfigLayer::new(LayerKind::Local, directory.join("a.json")));
+        assert!(loader.load().is_err());
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn not_found() {
+        let directory = temp_directory("not-found");
+        let mut loader = ConfigLoader::new(Version::new("1.1.0"));
+        loader.add_layer(ConfigLayer::new(
+            LayerKind::Local,
+            directory.join("config.json"),
+        ));
+        assert!(loader.load().is_err());
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,314 @@
+use serde_json::{Map, Value};
+
+use super::{migrate_action_kind, AppError, Config, Version};
+
+pub type MigrationStep = fn(&mut Map<String, Value>);
+
+pub struct Migration {
+    // version of the configure shape produced by this step
+    target: Version,
+    description: &'static str,
+    step: MigrationStep,
+}
+
+impl Migration {
+    pub fn new(target: &'static str, description: &'static str, step: MigrationStep) -> Self {
+        Migration {
+            target: Version::new(target),
+            description,
+            step,
+        }
+    }
+
+    pub fn get_target(&self) -> &Version {
+        &self.target
+    }
+
+    pub fn get_description(&self) -> &str {
+        self.description
+    }
+}
+
+pub struct ConfigMigrator {
+    current: Version,
+    migrations: Vec<Migration>,
+}
+
+impl ConfigMigrator {
+    pub fn new(current: Version) -> Self {
+        let mut migrator = ConfigMigrator {
+            current,
+            migrations: Vec::new(),
+        };
+        migrator.add(Migration::new(
+            "1.1.0",
+            "add configure version",
+            |_config| {},
+        ));
+        migrator.add(Migration::new(
+            "1.2.0",
+            "replace exec and switch_profile with action kind",
+            |config| {
+                fn migrate_actions(actions: Option<&mut Value>) {
+                    if let Some(Value::Array(actions)) = actions {
+                        for action in actions.iter_mut() {
+                            if let Value::Object(action) = action {
+                                migrate_action_kind(action);
+                            }
+                        }
+                    }
+                }
+                migrate_actions(config.get_mut("actions"));
+                if let Some(Value::Array(profiles)) = config.get_mut("profiles") {
+                    for profile in profiles.iter_mut() {
+                        migrate_actions(profile.get_mut("actions"));
+                    }
+                }
+            },
+        ));
+        migrator
+    }
+
+    pub fn add(&mut self, migration: Migration) {
+        self.migrations.push(migration);
+        self.migrations.sort_by(|l, r| l.target.cmp(&r.target));
+    }
+
+    pub fn migrate(&self, source: &Value) -> Result<MigrationReport, AppError> {
+        let mut object = match source {
+            Value::Object(object) => object.clone(),
+            _ => return Err(AppError::new("Configure must be a json object".to_string())),
+        };
+        let from = match object.get("version") {
+            Some(Value::String(version)) => Version::parse(version)?,
+            Some(_) => {
+                return Err(AppError::new(
+                    "Configure version must be a string".to_string(),
+                ))
+            }
+            None => Config::legacy_version(),
+        };
+        if from > self.current {
+            return Err(AppError::new(format!(
+                "Configure version {} is newer than ektox supports ({}), please upgrade ektox",
+                from, self.current
+            )));
+        }
+
+        let mut applied = Vec::new();
+        for migration in self.migrations.iter() {
+            if migration.target > from && migration.target <= self.current {
+                (migration.step)(&mut object);
+                applied.push(format!("{}: {}", migration.target, migration.description));
+            }
+        }
+        // a configure no step changed keeps its version, so it is not
+        // rewritten on every upgrade of ektox
+        let changed = Some(&object) != source.as_object();
+        if changed && from < self.current {
+            object.insert(
+                "version".to_string(),
+                Value::String(self.current.to_string()),
+            );
+        }
+
+        Ok(MigrationReport {
+            from,
+            to: self.current.clone(),
+            applied,
+            original: source.clone(),
+            migrated: Value::Object(object),
+        })
+    }
+}
+
+pub struct MigrationReport {
+    from: Version,
+    to: Version,
+    applied: Vec<String>,
+    original: Value,
+    migrated: Value,
+}
+
+impl MigrationReport {
+    pub fn is_required(&self) -> bool {
+        self.original != self.migrated
+    }
+
+    pub fn get_from(&self) -> &Version {
+        &self.from
+    }
+
+    pub fn get_to(&self) -> &Version {
+        &self.to
+    }
+
+    pub fn get_applied(&self) -> &Vec<String> {
+        &self.applied
+    }
+
+    pub fn get_migrated(&self) -> &Value {
+        &self.migrated
+    }
+
+    pub fn into_config(self) -> Result<Config, AppError> {
+        serde_json::from_value(self.migrated).map_err(|error| AppError::new(error.to_string()))
+    }
+
+    // list changes between original and migrated configure, one line per changed path
+    // like:
+    // + /version: "1.1.0"
+    // - /actions/0/exec: "C:/test.exe"
+    // ~ /startup: true -> false
+    pub fn diff(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        diff_value("", &self.original, &self.migrated, &mut lines);
+        lines
+    }
+
+    pub fn summary(&self) -> String {
+        if !self.is_required() {
+            return format!("Configure version {} is up to date", self.from);
+        }
+        let mut summary = format!("Migrate configure from {} to {}\n", self.from, self.to);
+        for step in self.applied.iter() {
+            summary.push_str(&format!("step {}\n", step));
+        }
+        for line in self.diff() {
+            summary.push_str(&line);
+            summary.push('\n');
+        }
+        summary
+    }
+}
+
+fn diff_value(path: &str, old: &Value, new: &Value, lines: &mut Vec<String>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old.iter() {
+                let child = format!("{}/{}", path, key);
+                match new.get(key) {
+                    Some(new_value) => diff_value(&child, old_value, new_value, lines),
+                    None => lines.push(format!("- {}: {}", child, old_value)),
+                }
+            }
+            for (key, new_value) in new.iter() {
+                if !old.contains_key(key) {
+                    lines.push(format!("+ {}/{}: {}", path, key, new_value));
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for index in 0..old.len().max(new.len()) {
+                let child = format!("{}/{}", path, index);
+                match (old.get(index), new.get(index)) {
+                    (Some(old_value), Some(new_value)) => {
+                        diff_value(&child, old_value, new_value, lines)
+                    }
+                    (Some(old_value), None) => lines.push(format!("- {}: {}", child, old_value)),
+                    (None, Some(new_value)) => lines.push(format!("+ {}: {}", child, new_value)),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ => {
+            if old != new {
+                lines.push(format!("~ {}: {} -> {}", path, old, new));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Config, ConfigMigrator, Migration, Version};
+
+    #[test]
+    fn it_works() {
+        let source = json!({
+            "startup": true,
+            "actions": [{ "hotkey": "ctrl + 1", "exec": "C:/test.exe" }]
+        });
+        let report = ConfigMigrator::new(Version::new("1.2.0"))
+            .migrate(&source)
+            .unwrap();
+        assert!(report.is_required());
+        assert_eq!(report.get_from(), &Version::new("1.0.0"));
+        assert_eq!(report.get_applied().len(), 2);
+        assert_eq!(
+            report.diff(),
+            vec!["+ /actions/0/kind: \"focus\"", "+ /version: \"1.2.0\""]
+        );
+        let config = report.into_config().unwrap();
+        assert_eq!(config.get_version(), &Version::new("1.2.0"));
+    }
+
+    #[test]
+    fn up_to_date() {
+        let source = json!({ "version": "1.1.0", "startup": true, "actions": [] });
+        let report = ConfigMigrator::new(Version::new("1.1.0"))
+            .migrate(&source)
+            .unwrap();
+        assert!(!report.is_required());
+        assert!(report.diff().is_empty());
+        assert!(report.get_applied().is_empty());
+    }
+
+    #[test]
+    fn version_only() {
+        let source = json!({ "version": "1.2.0", "startup": true, "actions": [] });
+        let report = ConfigMigrator::new(Version::new("1.3.0"))
+            .migrate(&source)
+            .unwrap();
+        assert!(!report.is_required());
+        assert!(report.diff().is_empty());
+        assert_eq!(
+            report.into_config().unwrap().get_version(),
+            &Version::new("1.2.0")
+        );
+    }
+
+    // the schema version is raised together with the last migration
+    #[test]
+    fn schema_version() {
+        let migrator = ConfigMigrator::new(Config::schema_version());
+        let last = migrator.migrations.last().unwrap();
+        assert_eq!(last.get_target(), &Config::schema_version());
+    }
+
+    #[test]
+    fn newer_than_running() {
+        let source = json!({ "version": "9.0.0", "startup": true, "actions": [] });
+        let result = ConfigMigrator::new(Version::new("1.1.0")).migrate(&source);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn step_by_step() {
+        let mut migrator = ConfigMigrator::new(Version::new("1.3.0"));
+        migrator.add(Migration::new("1.3.0", "rename startup", |config| {
+            if let Some(startup) = config.remove("autostart") {
+                config.insert("startup".to_string(), startup);
+            }
+        }));
+        migrator.add(Migration::new("1.2.0", "rename run", |config| {
+            if let Some(startup) = config.remove("run_at_login") {
+                config.insert("autostart".to_string(), startup);
+            }
+        }));
+        let source = json!({ "version": "1.1.0", "run_at_login": false, "actions": [] });
+        let report = migrator.migrate(&source).unwrap();
+        // built-in 1.2.0 step runs as well
+        assert_eq!(report.get_applied().len(), 3);
+        assert_eq!(
+            report.diff(),
+            vec![
+                "~ /version: \"1.1.0\" -> \"1.3.0\"",
+                "- /run_at_login: false",
+                "+ /startup: false",
+            ]
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utils::FrontendFilter;
+
+// which windows ektox considers at all, by default only windows a user would
+// switch to with alt + tab, set globally and per action, unset fields fall back
+// to the global value and then to the default
+// like:
+// "eligibility": { "include_owned": true, "include_cloaked": true }
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Eligibility {
+    // windows owned by another window, like dialogs and some electron apps
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_owned: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_untitled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_popups: Option<bool>,
+    // floating windows, like the windows of tray apps
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_tool_windows: Option<bool>,
+    // windows on other virtual desktops and suspended store apps, included
+    // unless set to false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_cloaked: Option<bool>,
+}
+
+impl Eligibility {
+    pub fn is_empty(&self) -> bool {
+        self == &Eligibility::default()
+    }
+
+    // fields set in other take precedence
+    pub fn merge(&self, other: &Eligibility) -> Eligibility {
+        Eligibility {
+            include_owned: other.include_owned.or(self.include_owned),
+            include_untitled: other.include_untitled.or(self.include_untitled),
+            include_popups: other.include_popups.or(self.include_popups),
+            include_tool_windows: other.include_tool_windows.or(self.include_tool_windows),
+            include_cloaked: other.include_cloaked.or(self.include_cloaked),
+        }
+    }
+
+    pub fn get_filter(&self) -> FrontendFilter {
+        let default = FrontendFilter::default();
+        let rule =
+            |include: Option<bool>, default: bool| include.map_or(default, |include| !include);
+        FrontendFilter {
+            no_owner: rule(self.include_owned, default.no_owner),
+            has_title: rule(self.include_untitled, default.has_title),
+            not_popup: rule(self.include_popups, default.not_popup),
+            not_tool_window: rule(self.include_tool_windows, default.not_tool_window),
+            not_cloaked: rule(self.include_cloaked, default.not_cloaked),
+            ..default
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::utils::FrontendFilter;
+
+    use super::Eligibility;
+
+    #[test]
+    fn it_works() {
+        let global: Eligibility =
+            serde_json::from_value(json!({ "include_owned": true, "include_cloaked": true }))
+                .unwrap();
+        let action: Eligibility =
+            serde_json::from_value(json!({ "include_cloaked": false, "include_popups": true }))
+                .unwrap();
+        let filter = global.merge(&action).get_filter();
+        assert_eq!(
+            filter,
+            FrontendFilter {
+                no_owner: false,
+                not_popup: false,
+                not_cloaked: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            Eligibility::default().get_filter(),
+            FrontendFilter::default()
+        );
+        assert!(serde_json::from_value::<Eligibility>(json!({ "owned": true })).is_err());
+    }
+}
@@ -0,0 +1,1393 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    process::Command,
+    time::{Duration, Instant},
+};
+
+use windows::Win32::Foundation::HWND;
+
+use crate::utils::{WindowBackend, WindowEvent, WindowInfo};
+
+use regex::Regex;
+
+use super::{
+    is_same_path, normalize_path, Action, ActionKind, AppError, Config, Cycle, FocusNavigation,
+    FocusOptions, Launch, NumberedBindings, NumberedGroup, NumberedOrder, OnActive, Select, Target,
+    WindowHistory, WindowMark, WindowMarks, WindowRegistry,
+};
+
+// how often every window is enumerated again in case window events were missed
+const SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+// a target launched without waiting is not launched again for this long,
+// against pressing the hotkey twice
+const LAUNCH_GUARD: Duration = Duration::from_secs(2);
+
+// windows remembered by the activation history
+pub const HISTORY_CAPACITY: usize = 64;
+
+// what the app has to do after an action, actions changing the app itself
+// cannot be done by the executor
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Done,
+    // target has no window
+    NoWindow,
+    // target was launched and its first window has not appeared yet
+    Pending,
+    // None for the base actions
+    SwitchProfile(Option<String>),
+    Reload,
+}
+
+// a launched target waiting for its first window
+struct PendingLaunch {
+    target: Target,
+    deadline: Instant,
+    // focus the first window once it appears
+    focus: bool,
+}
+
+// windows of a target being cycled through, most recently used first
+// when the cycle started
+struct CycleState {
+    target: Target,
+    windows: Vec<HWND>,
+    last: Instant,
+}
+
+pub struct Executor {
+    backend: Box<dyn WindowBackend>,
+    registry: WindowRegistry,
+    history: WindowHistory,
+    // history changed since it was last taken for saving
+    history_changed: bool,
+    navigation: FocusNavigation,
+    marks: WindowMarks,
+    // marks changed since they were last taken for saving
+    marks_changed: bool,
+    pending: Vec<PendingLaunch>,
+    cycle: Option<CycleState>,
+    // foreground window before target was brought forward
+    previous: Option<(Target, HWND)>,
+    // window focused last by each hotkey
+    last_focused: HashMap<String, HWND>,
+    // order in which windows were first seen, by handle
+    first_seen: HashMap<isize, u64>,
+    seen_count: u64,
+}
+
+impl Executor {
+    pub fn new(mut backend: Box<dyn WindowBackend>) -> Self {
+        let mut registry = WindowRegistry::new(SWEEP_INTERVAL);
+        registry.update(backend.as_mut(), Instant::now());
+        let mut navigation = FocusNavigation::new(Config::default_navigation_depth());
+        let foreground = backend.get_foreground_window();
+        if registry.get(foreground).is_some() {
+            navigation.visit(foreground);
+        }
+        Executor {
+            backend,
+            registry,
+            history: WindowHistory::new(HISTORY_CAPACITY),
+            history_changed: false,
+            navigation,
+            marks: WindowMarks::new(),
+            marks_changed: false,
+            pending: Vec::new(),
+            cycle: None,
+            previous: None,
+            last_focused: HashMap::new(),
+            first_seen: HashMap::new(),
+            seen_count: 0,
+        }
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    pub fn get_backend(&self) -> &dyn WindowBackend {
+        self.backend.as_ref()
+    }
+
+    pub fn get_history(&self) -> &WindowHistory {
+        &self.history
+    }
+
+    // history saved by a previous run, windows which no longer exist are dropped
+    pub fn set_history(&mut self, mut history: WindowHistory) {
+        let registry = &self.registry;
+        history.retain(|entry| {
+            registry
+                .get(entry.get_hwnd())
+                .is_some_and(|window| is_same_path(window.get_path(), entry.get_path()))
+        });
+        self.history = history;
+    }
+
+    // true once after the history changed
+    pub fn take_history_changed(&mut self) -> bool {
+        std::mem::take(&mut self.history_changed)
+    }
+
+    pub fn get_marks(&self) -> &WindowMarks {
+        &self.marks
+    }
+
+    // marks saved by a previous run, found again when jumped to
+    pub fn set_marks(&mut self, marks: WindowMarks) {
+        self.marks = marks;
+    }
+
+    // true once after marks changed
+    pub fn take_marks_changed(&mut self) -> bool {
+        std::mem::take(&mut self.marks_changed)
+    }
+
+    // apply window events to the registry and the history
+    pub fn refresh(&mut self, now: Instant) {
+        let events = self.backend.take_events();
+        let swept = self.registry.apply(self.backend.as_ref(), &events, now);
+        let before = self.history.clone();
+        for event in events.iter() {
+            match event {
+                // windows which cannot be switched to are not remembered
+                WindowEvent::Foreground(hwnd) => {
+                    if let Some(window) = self.registry.get(*hwnd) {
+                        self.history.activate(*hwnd, window.get_path());
+                        self.navigation.visit(*hwnd);
+                    }
+                }
+                WindowEvent::Destroyed(hwnd) => {
+                    self.history.remove(*hwnd);
+                    self.navigation.remove(*hwnd);
+                }
+                _ => {}
+            }
+        }
+        if swept {
+            let registry = &self.registry;
+            self.history
+                .retain(|entry| registry.get(entry.get_hwnd()).is_some());
+            let gone: Vec<HWND> = self
+                .navigation
+                .get_windows()
+                .iter()
+                .filter(|hwnd| registry.get(**hwnd).is_none())
+                .copied()
+                .collect();
+            for hwnd in gone {
+                self.navigation.remove(hwnd);
+            }
+        }
+        if self.history != before {
+            self.history_changed = true;
+        }
+    }
+
+    pub fn execute(&mut self, config: &Config, action: &Action) -> Result<Outcome, AppError> {
+        self.execute_at(config, action, Instant::now())
+    }
+
+    pub fn execute_at(
+        &mut self,
+        config: &Config,
+        action: &Action,
+        now: Instant,
+    ) -> Result<Outcome, AppError> {
+        self.refresh(now);
+        self.navigation.set_depth(config.navigation_depth);
+        match &action.kind {
+            ActionKind::Focus { target, focus } => {
+                Ok(self.focus(config, &action.hotkey.to_string(), target, focus, now))
+            }
+            ActionKind::Launch { target, launch } => {
+                self.launch(config, target, launch)?;
+                Ok(Outcome::Done)
+            }
+            ActionKind::LaunchOrFocus {
+                target,
+                launch,
+                focus,
+            } => {
+                if !self.find_windows(config, target).is_empty() {
+                    self.pending.retain(|pending| &pending.target != target);
+                    let key = action.hotkey.to_string();
+                    return Ok(self.focus(config, &key, target, focus, now));
+                }
+                if self
+                    .pending
+                    .iter()
+                    .any(|pending| &pending.target == target && pending.deadline > now)
+                {
+                    return Ok(Outcome::Pending);
+                }
+                self.launch(config, target, launch)?;
+                let timeout = if launch.wait {
+                    launch.get_timeout()
+                } else {
+                    LAUNCH_GUARD.min(launch.get_timeout())
+                };
+                self.pending.retain(|pending| &pending.target != target);
+                self.pending.push(PendingLaunch {
+                    target: target.clone(),
+                    deadline: now + timeout,
+                    focus: launch.wait,
+                });
+                Ok(Outcome::Done)
+            }
+            ActionKind::Run { command, launch } => {
+                self.spawn(command, launch)?;
+                Ok(Outcome::Done)
+            }
+            ActionKind::Close { target } => {
+                self.apply(config, target, |backend, window| backend.close(window))
+            }
+            ActionKind::Minimize { target } => {
+                self.apply(config, target, |backend, window| backend.minimize(window))
+            }
+            ActionKind::Maximize { target } => {
+                self.apply(config, target, |backend, window| backend.maximize(window))
+            }
+            ActionKind::Toggle { target } => {
+                let focus = FocusOptions {
+                    on_active: OnActive::Minimize,
+                    ..Default::default()
+                };
+                Ok(self.focus(config, &action.hotkey.to_string(), target, &focus, now))
+            }
+            ActionKind::SendKeys { keys } => {
+                self.backend.send_keys(keys);
+                Ok(Outcome::Done)
+            }
+            ActionKind::Open { path } => {
+                let (program, args) = if cfg!(windows) {
+                    // empty title is required by start when path is quoted
+                    ("cmd", vec!["/C", "start", "", path.as_str()])
+                } else {
+                    ("xdg-open", vec![path.as_str()])
+                };
+                let mut command = Command::new(program);
+                command.args(args);
+                self.backend
+                    .spawn(command)
+                    .map_err(|error| AppError::new(format!("Open '{}' Failed: {}", path, error)))?;
+                Ok(Outcome::Done)
+            }
+            ActionKind::Mark { slot, title } => {
+                let foreground = self.backend.get_foreground_window();
+                if foreground.0 == 0 {
+                    return Ok(Outcome::NoWindow);
+                }
+                let window = match self.registry.get(foreground) {
+                    Some(window) => window.clone(),
+                    None => self.backend.describe(foreground),
+                };
+                self.marks
+                    .set(*slot, WindowMark::new(&window, title.as_ref()));
+                self.marks_changed = true;
+                Ok(Outcome::Done)
+            }
+            ActionKind::JumpToMark { slot } => {
+                let before = self.marks.get(*slot).cloned();
+                let window = self.marks.resolve(*slot, &self.registry.snapshot());
+                if self.marks.get(*slot) != before.as_ref() {
+                    self.marks_changed = true;
+                }
+                match window {
+                    Some(window) => {
+                        self.backend.focus(window);
+                        Ok(Outcome::Done)
+                    }
+                    None => Ok(Outcome::NoWindow),
+                }
+            }
+            ActionKind::Pick => {
+                let error = |error: std::io::Error| {
+                    AppError::new(format!("Start Window Picker Failed: {}", error))
+                };
+                let mut command = Command::new(std::env::current_exe().map_err(error)?);
+                command.args(["pick", "--tui"]);
+                self.backend.spawn(command).map_err(error)?;
+                Ok(Outcome::Done)
+            }
+            ActionKind::FocusNumber { number } => match &config.numbered {
+                Some(numbered) => self.focus_number(config, numbered, *number),
+                None => Ok(Outcome::NoWindow),
+            },
+            ActionKind::Back => Ok(self.navigate(|navigation| navigation.back())),
+            ActionKind::Forward => Ok(self.navigate(|navigation| navigation.forward())),
+            ActionKind::SwitchProfile { profile } => Ok(Outcome::SwitchProfile(profile.clone())),
+            ActionKind::Reload => Ok(Outcome::Reload),
+        }
+    }
+
+    // focus the window back or forward returns
+    fn navigate(&mut self, step: fn(&mut FocusNavigation) -> Option<HWND>) -> Outcome {
+        match step(&mut self.navigation) {
+            Some(window) => {
+                self.backend.focus(window);
+                Outcome::Done
+            }
+            None => Outcome::NoWindow,
+        }
+    }
+
+    // focus first windows of launched targets, give up on those timed out,
+    // called periodically while there are pending launches
+    pub fn poll(&mut self, config: &Config, now: Instant) {
+        self.refresh(now);
+        let pending = std::mem::take(&mut self.pending);
+        for launch in pending {
+            if let Some(window) = self.find_windows(config, &launch.target).first() {
+                if launch.focus {
+                    self.backend.focus(*window);
+                }
+            } else if launch.deadline > now {
+                self.pending.push(launch);
+            }
+        }
+    }
+
+    // focus the selected window of target, apply the on_active policy if
+    // target is already foreground, key is the hotkey of the action
+    fn focus(
+        &mut self,
+        config: &Config,
+        key: &str,
+        target: &Target,
+        options: &FocusOptions,
+        now: Instant,
+    ) -> Outcome {
+        let windows = self.find_windows(config, target);
+        if windows.is_empty() {
+            return Outcome::NoWindow;
+        }
+        let windows = self.select(key, windows, &options.select);
+        let cycle = &options.cycle;
+        let foreground = self.backend.get_foreground_window();
+        if !windows.contains(&foreground) {
+            self.cycle = None;
+            self.previous = Some((target.clone(), foreground)).filter(|_| foreground.0 != 0);
+            let window = if cycle.reverse {
+                windows[windows.len() - 1]
+            } else {
+                windows[0]
+            };
+            self.backend.focus(window);
+            self.last_focused.insert(key.to_string(), window);
+            return Outcome::Done;
+        }
+        match options.on_active {
+            OnActive::Cycle => {
+                let window = self.cycle_next(target, &windows, foreground, cycle, now);
+                self.backend.focus(window);
+                self.last_focused.insert(key.to_string(), window);
+            }
+            OnActive::Minimize => self.backend.minimize(foreground),
+            OnActive::ReturnToPrevious => {
+                let all = self.registry.snapshot().get_hwnds();
+                let previous = match self.previous.take() {
+                    Some((previous_target, window))
+                        if &previous_target == target && all.contains(&window) =>
+                    {
+                        Some(window)
+                    }
+                    // not brought forward by ektox, the window used before target
+                    _ => self
+                        .history
+                        .get_recent()
+                        .into_iter()
+                        .find(|window| !windows.contains(window) && all.contains(window)),
+                };
+                match previous {
+                    Some(window) => self.backend.focus(window),
+                    None => self.backend.minimize(foreground),
+                }
+            }
+            OnActive::Nothing => {}
+        }
+        Outcome::Done
+    }
+
+    // windows seen at the same time are numbered from the bottom of z-order,
+    // handles of closed windows may be reused so they are forgotten
+    fn update_first_seen(&mut self) {
+        let all = self.registry.snapshot().get_hwnds();
+        self.first_seen
+            .retain(|hwnd, _| all.iter().any(|window| window.0 == *hwnd));
+        for hwnd in all.iter().rev() {
+            if !self.first_seen.contains_key(&hwnd.0) {
+                self.first_seen.insert(hwnd.0, self.seen_count);
+                self.seen_count += 1;
+            }
+        }
+    }
+
+    // focus the windows numbered number, the next of them if one is already
+    // foreground, launch a pinned app without windows
+    fn focus_number(
+        &mut self,
+        config: &Config,
+        numbered: &NumberedBindings,
+        number: usize,
+    ) -> Result<Outcome, AppError> {
+        let slots = self.get_numbered(config, numbered);
+        let (target, windows) = match number.checked_sub(1).and_then(|index| slots.get(index)) {
+            Some(slot) => slot,
+            None => return Ok(Outcome::NoWindow),
+        };
+        if windows.is_empty() {
+            return match target {
+                Some(target) if target.is_launchable() => {
+                    self.launch(config, target, &Launch::default())?;
+                    Ok(Outcome::Done)
+                }
+                _ => Ok(Outcome::NoWindow),
+            };
+        }
+        let foreground = self.backend.get_foreground_window();
+        let next = match windows.iter().position(|window| *window == foreground) {
+            Some(index) => windows[(index + 1) % windows.len()],
+            None => windows[0],
+        };
+        self.backend.focus(next);
+        Ok(Outcome::Done)
+    }
+
+    // windows of each number in order, with the pinned target a number stands for,
+    // windows of an app keep z-order
+    fn get_numbered(
+        &mut self,
+        config: &Config,
+        numbered: &NumberedBindings,
+    ) -> Vec<(Option<Target>, Vec<HWND>)> {
+        self.update_first_seen();
+        let snapshot = self.registry.snapshot();
+        let first_seen = |hwnd: HWND| self.first_seen.get(&hwnd.0).copied().unwrap_or(u64::MAX);
+        let pinned: Vec<Target> = numbered
+            .get_pinned()
+            .iter()
+            .map(|pinned| config.get_pinned_target(pinned))
+            .collect();
+        // rules are checked when configure is loaded
+        let matchers: Vec<_> = pinned
+            .iter()
+            .map(|target| config.get_matcher(target).ok())
+            .collect();
+        let pin = |window: &WindowInfo| {
+            matchers
+                .iter()
+                .position(|matcher| matcher.as_ref().is_some_and(|m| m.is_match(window)))
+        };
+        let (pinned_windows, windows): (Vec<&WindowInfo>, Vec<&WindowInfo>) =
+            snapshot.iter().partition(|window| pin(window).is_some());
+
+        let mut slots: Vec<(Option<Target>, Vec<HWND>)> = Vec::new();
+        match numbered.group {
+            NumberedGroup::Window => {
+                let mut pinned_windows = pinned_windows;
+                pinned_windows.sort_by_key(|window| (pin(window), first_seen(window.hwnd)));
+                let mut windows = windows;
+                match numbered.order {
+                    NumberedOrder::Alphabetical => {
+                        windows.sort_by_key(|window| window.get_title().to_lowercase())
+                    }
+                    _ => windows.sort_by_key(|window| first_seen(window.hwnd)),
+                }
+                for window in pinned_windows.into_iter().chain(windows) {
+                    slots.push((None, vec![window.hwnd]));
+                }
+            }
+            NumberedGroup::App => {
+                for (index, target) in pinned.into_iter().enumerate() {
+                    let windows = pinned_windows
+                        .iter()
+                        .filter(|window| pin(window) == Some(index))
+                        .map(|window| window.hwnd)
+                        .collect();
+                    slots.push((Some(target), windows));
+                }
+                // by lowercase image path
+                let mut apps: Vec<(String, Vec<&WindowInfo>)> = Vec::new();
+                for window in windows {
+                    let path = normalize_path(window.get_path()).to_lowercase();
+                    match apps.iter_mut().find(|(app, _)| *app == path) {
+                        Some((_, windows)) => windows.push(window),
+                        None => apps.push((path, vec![window])),
+                    }
+                }
+                match numbered.order {
+                    NumberedOrder::Alphabetical => {
+                        apps.sort_by_key(|(_, windows)| windows[0].get_basename().to_lowercase())
+                    }
+                    _ => apps.sort_by_key(|(_, windows)| {
+                        windows.iter().map(|window| first_seen(window.hwnd)).min()
+                    }),
+                }
+                for (_, windows) in apps {
+                    slots.push((None, windows.iter().map(|window| window.hwnd).collect()));
+                }
+            }
+        }
+        slots
+    }
+
+    // order windows of target by the select strategy, ties keep z-order
+    fn select(&mut self, key: &str, mut windows: Vec<HWND>, select: &Select) -> Vec<HWND> {
+        self.update_first_seen();
+        let backend = self.backend.as_ref();
+        let first_seen = |hwnd: &HWND| self.first_seen.get(&hwnd.0).copied().unwrap_or(u64::MAX);
+        let history = &self.history;
+        match select {
+            // windows never focused while ektox ran follow in z-order
+            Select::MostRecentlyUsed => {
+                windows.sort_by_key(|hwnd| history.get_rank(*hwnd).unwrap_or(usize::MAX))
+            }
+            Select::CurrentMonitor => {
+                let monitor = backend.get_monitor(backend.get_foreground_window());
+                windows.sort_by_key(|hwnd| backend.get_monitor(*hwnd) != monitor);
+            }
+            Select::Largest => {
+                windows.sort_by_key(|hwnd| Reverse(backend.get_rect(*hwnd).get_area()))
+            }
+            Select::Oldest => windows.sort_by_key(first_seen),
+            Select::Newest => windows.sort_by_key(|hwnd| Reverse(first_seen(hwnd))),
+            Select::LastFocused => {
+                if let Some(last) = self.last_focused.get(key) {
+                    windows.sort_by_key(|hwnd| hwnd != last);
+                }
+            }
+            Select::PreferTitle(pattern) => {
+                // checked when configure is loaded
+                if let Ok(regex) = Regex::new(pattern) {
+                    windows.sort_by_key(|hwnd| !regex.is_match(&backend.get_title(*hwnd)));
+                }
+            }
+        }
+        windows
+    }
+
+    // next window of target after the foreground one, the order of the first
+    // press is most recently used first and kept until the cycle timeout, as
+    // every focus changes the history
+    fn cycle_next(
+        &mut self,
+        target: &Target,
+        windows: &[HWND],
+        foreground: HWND,
+        cycle: &Cycle,
+        now: Instant,
+    ) -> HWND {
+        let mut state = match self.cycle.take() {
+            Some(state)
+                if &state.target == target
+                    && now.duration_since(state.last) < cycle.get_timeout() =>
+            {
+                state
+            }
+            _ => {
+                let mut windows = windows.to_vec();
+                windows.sort_by_key(|hwnd| self.history.get_rank(*hwnd).unwrap_or(usize::MAX));
+                CycleState {
+                    target: target.clone(),
+                    windows,
+                    last: now,
+                }
+            }
+        };
+        state.windows.retain(|window| windows.contains(window));
+        for window in windows.iter() {
+            if !state.windows.contains(window) {
+                state.windows.push(*window);
+            }
+        }
+        let count = state.windows.len();
+        let index = state
+            .windows
+            .iter()
+            .position(|window| *window == foreground)
+            .unwrap_or(0);
+        let next = if cycle.reverse {
+            (index + count - 1) % count
+        } else {
+            (index + 1) % count
+        };
+        let window = state.windows[next];
+        state.last = now;
+        self.cycle = Some(state);
+        window
+    }
+
+    // windows of target in z-order
+    pub fn find_windows(&self, config: &Config, target: &Target) -> Vec<HWND> {
+        // rules are checked when configure is loaded
+        let matcher = match config.get_matcher(target) {
+            Ok(matcher) => matcher,
+            Err(_) => return Vec::new(),
+        };
+        // the registry only knows windows of the default rules
+        let filter = config.eligibility.merge(&target.eligibility).get_filter();
+        let windows = if &filter == self.registry.get_filter() {
+            self.registry.snapshot()
+        } else {
+            self.backend.snapshot_with(&filter)
+        };
+        windows
+            .filter(|window| matcher.is_match(window))
+            .get_hwnds()
+    }
+
+    // apply to the first window of target, or the foreground window without target
+    fn apply(
+        &mut self,
+        config: &Config,
+        target: &Target,
+        operation: fn(&mut dyn WindowBackend, HWND),
+    ) -> Result<Outcome, AppError> {
+        let window = if target.is_empty() {
+            Some(self.backend.get_foreground_window()).filter(|hwnd| hwnd.0 != 0)
+        } else {
+            self.find_windows(config, target).first().copied()
+        };
+        match window {
+            Some(window) => {
+                operation(self.backend.as_mut(), window);
+                Ok(Outcome::Done)
+            }
+            None => Ok(Outcome::NoWindow),
+        }
+    }
+
+    fn launch(
+        &mut self,
+        config: &Config,
+        target: &Target,
+        launch: &Launch,
+    ) -> Result<(), AppError> {
+        let program = match (&target.exec, &target.target) {
+            (Some(exec), _) => exec.clone(),
+            (None, Some(name)) => match config.get_app(name).and_then(|app| app.get_launch()) {
+                Some(program) => program.clone(),
+                None => {
+                    return Err(AppError::new(format!(
+                        "App '{}' Has Nothing To Launch",
+                        name
+                    )))
+                }
+            },
+            (None, None) => return Err(AppError::new("Nothing To Launch".to_string())),
+        };
+        self.spawn(&program, launch)
+    }
+
+    fn build_command(program: &str, launch: &Launch) -> Command {
+        let mut command = Command::new(program);
+        command.args(&launch.args).envs(&launch.env);
+        if let Some(cwd) = &launch.cwd {
+            command.current_dir(cwd);
+        }
+        command
+    }
+
+    fn spawn(&mut self, program: &str, launch: &Launch) -> Result<(), AppError> {
+        self.backend
+            .spawn(Self::build_command(program, launch))
+            .map_err(|error| AppError::new(format!("Launch '{}' Failed: {}", program, error)))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::Path,
+        time::{Duration, Instant},
+    };
+
+    use serde_json::json;
+    use windows::Win32::Foundation::HWND;
+
+    use crate::{
+        common::{Config, WindowHistory},
+        utils::{FakeBackend, Rect, WindowState},
+    };
+
+    use super::{ActionKind, Executor, Launch, Outcome, Target};
+
+    fn config() -> Config {
+        serde_json::from_value(json!({
+            "version": "1.2.0",
+            "startup": false,
+            "apps": { "browser": { "exec": ["C:/chrome.exe", "C:/firefox.exe"] } },
+            "actions": [
+                { "hotkey": "ctrl + 1", "kind": "focus", "target": "browser" },
+                { "hotkey": "ctrl + 2", "kind": "launch_or_focus", "exec": "C:/code.exe", "args": ["-n"] },
+                { "hotkey": "ctrl + 3", "kind": "toggle", "exec": "C:/term.exe" },
+                { "hotkey": "ctrl + 4", "kind": "close" },
+                { "hotkey": "ctrl + 5", "kind": "send_keys", "keys": ["ctrl + c", "ctrl + v"] },
+                { "hotkey": "ctrl + 6", "kind": "switch_profile", "profile": "gaming" },
+                { "hotkey": "ctrl + 7", "kind": "maximize", "target": "browser" },
+                { "hotkey": "ctrl + 8", "kind": "pick" }
+            ],
+            "profiles": [{ "name": "gaming" }]
+        }))
+        .unwrap()
+    }
+
+    fn executor(backend: FakeBackend) -> Executor {
+        Executor::new(Box::new(backend))
+    }
+
+    fn foreground(executor: &Executor) -> isize {
+        executor.get_backend().get_foreground_window().0
+    }
+
+    #[test]
+    fn focus() {
+        let config = config();
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/code.exe");
+        backend.add_window(2, "C:/firefox.exe");
+        let mut executor = executor(backend);
+        let outcome = executor.execute(&config, &config.actions[0]).unwrap();
+        assert_eq!(outcome, Outcome::Done);
+        assert_eq!(executor.get_backend().get_foreground_window(), HWND(2));
+
+        let mut executor = self::executor(FakeBackend::new());
+        let outcome = executor.execute(&config, &config.actions[0]).unwrap();
+        assert_eq!(outcome, Outcome::NoWindow);
+    }
+
+    #[test]
+    fn launch_or_focus() {
+        let config = config();
+        let backend = FakeBackend::new();
+        let spawned = backend.spawned.clone();
+        let mut executor = executor(backend);
+        executor.execute(&config, &config.actions[1]).unwrap();
+        assert_eq!(*spawned.borrow(), vec!["C:/code.exe -n"]);
+
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/code.exe");
+        let spawned = backend.spawned.clone();
+        let mut executor = self::executor(backend);
+        executor.execute(&config, &config.actions[1]).unwrap();
+        assert_eq!(executor.get_backend().get_foreground_window(), HWND(1));
+        assert!(spawned.borrow().is_empty());
+    }
+
+    #[test]
+    fn pick() {
+        let config = config();
+        let backend = FakeBackend::new();
+        let spawned = backend.spawned.clone();
+        let mut executor = executor(backend);
+        executor.execute(&config, &config.actions[7]).unwrap();
+        assert!(spawned.borrow()[0].ends_with(" pick --tui"));
+    }
+
+    #[test]
+    fn cycle() {
+        let config: Config = serde_json::from_value(json!({
+            "startup": false,
+            "actions": [
+                { "hotkey": "alt + 1", "kind": "focus", "exec": "C:/code.exe" },
+                { "hotkey": "alt + shift + 1", "kind": "focus", "exec": "C:/code.exe", "reverse": true }
+            ]
+        }))
+        .unwrap();
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/code.exe");
+        backend.add_window(2, "C:/term.exe");
+        backend.add_window(3, "C:/code.exe");
+        backend.add_window(4, "C:/code.exe");
+        let mut executor = executor(backend);
+        let forward = &config.actions[0];
+        let reverse = &config.actions[1];
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+
+        executor.execute_at(&config, forward, at(0)).unwrap();
+        assert_eq!(foreground(&executor), 1);
+        executor.execute_at(&config, forward, at(100)).unwrap();
+        assert_eq!(foreground(&executor), 3);
+        executor.execute_at(&config, forward, at(200)).unwrap();
+        assert_eq!(foreground(&executor), 4);
+        executor.execute_at(&config, reverse, at(300)).unwrap();
+        assert_eq!(foreground(&executor), 3);
+        executor.execute_at(&config, forward, at(400)).unwrap();
+        executor.execute_at(&config, forward, at(500)).unwrap();
+        assert_eq!(foreground(&executor), 1);
+
+        executor.execute_at(&config, forward, at(600)).unwrap();
+        assert_eq!(foreground(&executor), 3);
+
+        // after the timeout cycling starts over from the most recently used
+        // window, so a single press goes back to the previous one
+        executor.execute_at(&config, forward, at(5000)).unwrap();
+        assert_eq!(foreground(&executor), 1);
+        executor.execute_at(&config, forward, at(10000)).unwrap();
+        assert_eq!(foreground(&executor), 3);
+        executor.execute_at(&config, forward, at(10100)).unwrap();
+        assert_eq!(foreground(&executor), 4);
+    }
+
+    #[test]
+    fn on_active() {
+        let config: Config = serde_json::from_value(json!({
+            "startup": false,
+            "actions": [
+                { "hotkey": "alt + 1", "kind": "focus", "exec": "C:/notes.exe", "on_active": "return_to_previous" },
+                { "hotkey": "alt + 2", "kind": "focus", "exec": "C:/notes.exe", "on_active": "minimize" },
+                { "hotkey": "alt + 3", "kind": "focus", "exec": "C:/notes.exe", "on_active": "nothing" }
+            ]
+        }))
+        .unwrap();
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/code.exe");
+        backend.add_window(2, "C:/term.exe");
+        backend.add_window(3, "C:/notes.exe");
+        backend.foreground = HWND(2);
+        let mut executor = executor(backend);
+
+        executor.execute(&config, &config.actions[0]).unwrap();
+        assert_eq!(foreground(&executor), 3);
+        executor.execute(&config, &config.actions[0]).unwrap();
+        assert_eq!(foreground(&executor), 2);
+        // the second press keeps target in front
+        executor.execute(&config, &config.actions[2]).unwrap();
+        executor.execute(&config, &config.actions[2]).unwrap();
+        assert_eq!(foreground(&executor), 3);
+        executor.execute(&config, &config.actions[0]).unwrap();
+        assert_eq!(foreground(&executor), 2);
+
+        executor.execute(&config, &config.actions[1]).unwrap();
+        executor.execute(&config, &config.actions[1]).unwrap();
+        assert!(executor.get_backend().is_minimized(HWND(3)));
+    }
+
+    #[test]
+    fn match_rule() {
+        let config: Config = serde_json::from_value(json!({
+            "startup": false,
+            "apps": { "discord": { "match": { "path": "C:/Users/*/Discord/app-*/Discord.exe" } } },
+            "actions": [
+                { "hotkey": "alt + 1", "kind": "focus", "target": "discord" },
+                { "hotkey": "alt + 2", "kind": "focus", "match": { "name": "code.exe", "title": "ektox" } }
+            ]
+        }))
+        .unwrap();
+        config.validate().unwrap();
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/Users/alice/Discord/app-1.0.9003/Discord.exe");
+        let other = backend.add_window(2, "C:/Apps/VS Code/Code.exe");
+        backend.set_window_title(other, "other - Visual Studio Code", "");
+        let ektox = backend.add_window(3, "C:/Apps/VS Code/Code.exe");
+        backend.set_window_title(ektox, "main.rs - ektox - Visual Studio Code", "");
+        let mut executor = executor(backend);
+        executor.execute(&config, &config.actions[0]).unwrap();
+        assert_eq!(executor.get_backend().get_foreground_window(), HWND(1));
+        executor.execute(&config, &config.actions[1]).unwrap();
+        assert_eq!(executor.get_backend().get_foreground_window(), HWND(3));
+    }
+
+    #[test]
+    fn query() {
+        let config: Config = serde_json::from_value(json!({
+            "startup": false,
+            "actions": [
+                { "hotkey": "alt + 1", "kind": "focus", "query": "exe:code.exe title~ektox monitor:2" }
+            ]
+        }))
+        .unwrap();
+        config.validate().unwrap();
+        let mut backend = FakeBackend::new();
+        let first = backend.add_window(1, "C:/Apps/Code.exe");
+        backend.set_window_title(first, "ektox - Visual Studio Code", "");
+        let second = backend.add_window(2, "C:/Apps/Code.exe");
+        backend.set_window_title(second, "ektox - Visual Studio Code", "");
+        backend.windows[1].monitor = 2;
+        let mut executor = executor(backend);
+        executor.execute(&config, &config.actions[0]).unwrap();
+        assert_eq!(executor.get_backend().get_foreground_window(), HWND(2));
+    }
+
+    #[test]
+    fn select() {
+        let config: Config = serde_json::from_value(json!({
+            "startup": false,
+            "actions": [
+                { "hotkey": "alt + 1", "kind": "focus", "exec": "C:/code.exe", "select": "largest" },
+                { "hotkey": "alt + 2", "kind": "focus", "exec": "C:/code.exe", "select": "current_monitor" },
+                { "hotkey": "alt + 3", "kind": "focus", "exec": "C:/code.exe", "select": "oldest" },
+                { "hotkey": "alt + 4", "kind": "focus", "exec": "C:/code.exe", "select": { "prefer_title": "^ektox" } },
+                { "hotkey": "alt + 5", "kind": "focus", "exec": "C:/code.exe", "select": "last_focused" }
+            ]
+        }))
+        .unwrap();
+        config.validate().unwrap();
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/term.exe");
+        backend.add_window(2, "C:/code.exe");
+        backend.add_window(3, "C:/code.exe");
+        backend.add_window(4, "C:/code.exe");
+        backend.windows[0].monitor = 2;
+        backend.windows[2].monitor = 2;
+        backend.windows[3].rect = Rect::new(0, 0, 1920, 1080);
+        backend.set_window_title(HWND(3), "ektox - code", "");
+        backend.foreground = HWND(1);
+        let mut executor = executor(backend);
+        let mut press = |index: usize| {
+            executor.execute(&config, &config.actions[index]).unwrap();
+            let window = foreground(&executor);
+            // go back to the terminal so the next press is not a cycle
+            executor.backend.focus(HWND(1));
+            window
+        };
+        assert_eq!(press(0), 4);
+        assert_eq!(press(1), 3);
+        // the bottom window of the first enumeration is the oldest
+        assert_eq!(press(2), 4);
+        assert_eq!(press(3), 3);
+        assert_eq!(press(4), 3);
+        assert_eq!(press(0), 4);
+        // most recently used is 4 now
+        assert_eq!(press(4), 3);
+
+        let invalid: Config = serde_json::from_value(json!({
+            "startup": false,
+            "actions": [
+                { "hotkey": "alt + 1", "kind": "focus", "exec": "C:/a.exe", "select": { "prefer_title": "(" } }
+            ]
+        }))
+        .unwrap();
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn launch_once() {
+        let config = config();
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/term.exe");
+        let spawned = backend.spawned.clone();
+        let mut executor = executor(backend);
+        executor.execute(&config, &config.actions[1]).unwrap();
+        assert!(executor.has_pending());
+        // pressed again while starting
+        assert_eq!(
+            executor.execute(&config, &config.actions[1]).unwrap(),
+            Outcome::Pending
+        );
+        assert_eq!(spawned.borrow().len(), 1);
+
+        // timed out, the next press launches again
+        executor.poll(&config, Instant::now() + Duration::from_secs(60));
+        assert!(!executor.has_pending());
+        executor.execute(&config, &config.actions[1]).unwrap();
+        assert_eq!(spawned.borrow().len(), 2);
+    }
+
+    #[test]
+    fn focus_first_window() {
+        let config = config();
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/term.exe");
+        backend.spawn_window = true;
+        let mut executor = executor(backend);
+        executor.execute(&config, &config.actions[1]).unwrap();
+        assert_eq!(executor.get_backend().get_foreground_window(), HWND(0));
+        executor.poll(&config, Instant::now());
+        assert!(!executor.has_pending());
+        assert_eq!(executor.get_backend().get_foreground_window(), HWND(1001));
+    }
+
+    #[test]
+    fn launch_without_wait() {
+        let config: Config = serde_json::from_value(json!({
+            "startup": false,
+            "actions": [
+                { "hotkey": "ctrl + 1", "kind": "launch_or_focus", "exec": "C:/code.exe", "wait": false }
+            ]
+        }))
+        .unwrap();
+        let backend = FakeBackend::new();
+        let spawned = backend.spawned.clone();
+        let mut launcher = executor(backend);
+        let now = Instant::now();
+        launcher
+            .execute_at(&config, &config.actions[0], now)
+            .unwrap();
+        // pressed twice quickly
+        assert_eq!(
+            launcher
+                .execute_at(&config, &config.actions[0], now)
+                .unwrap(),
+            Outcome::Pending
+        );
+        assert_eq!(spawned.borrow().len(), 1);
+        launcher.poll(&config, now + Duration::from_secs(3));
+        assert!(!launcher.has_pending());
+
+        // the first window is not focused
+        let mut backend = FakeBackend::new();
+        backend.spawn_window = true;
+        let mut launcher = executor(backend);
+        launcher
+            .execute_at(&config, &config.actions[0], now)
+            .unwrap();
+        launcher.poll(&config, now);
+        assert!(!launcher.has_pending());
+        assert_eq!(launcher.get_backend().get_foreground_window(), HWND(0));
+    }
+
+    #[test]
+    fn eligibility() {
+        let config: Config = serde_json::from_value(json!({
+            "startup": false,
+            "eligibility": { "include_cloaked": true },
+            "actions": [
+                { "hotkey": "alt + 1", "kind": "focus", "exec": "C:/app.exe" },
+                { "hotkey": "alt + 2", "kind": "focus", "exec": "C:/app.exe", "eligibility": { "include_owned": true } },
+                { "hotkey": "alt + 3", "kind": "focus", "exec": "C:/other.exe", "eligibility": { "include_cloaked": false } }
+            ]
+        }))
+        .unwrap();
+        let mut backend = FakeBackend::new();
+        let main = backend.add_window(1, "C:/app.exe");
+        let dialog = backend.add_window(2, "C:/app.exe");
+        backend.windows[1].owner = main;
+        let other = backend.add_window(3, "C:/other.exe");
+        backend.set_window_state(other, WindowState::Cloaked);
+        let mut executor = executor(backend);
+        let app = config.actions[0].kind.get_target().unwrap();
+        assert_eq!(executor.find_windows(&config, app), vec![main]);
+        let owned = config.actions[1].kind.get_target().unwrap();
+        assert_eq!(executor.find_windows(&config, owned), vec![main, dialog]);
+        // cloaked windows are included globally but not for this action
+        let outcome = executor.execute(&config, &config.actions[2]).unwrap();
+        assert_eq!(outcome, Outcome::NoWindow);
+        let other = Target::exec("C:/other.exe");
+        assert_eq!(executor.find_windows(&config, &other), vec![HWND(3)]);
+    }
+
+    #[test]
+    fn history() {
+        let config = config();
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/code.exe");
+        backend.add_window(2, "C:/firefox.exe");
+        backend.add_window(3, "C:/term.exe");
+        let mut executor = executor(backend);
+        executor.execute(&config, &config.actions[0]).unwrap();
+        executor.execute(&config, &config.actions[2]).unwrap();
+        // focus events of the last action are applied before the next one
+        executor.execute(&config, &config.actions[4]).unwrap();
+        let history = executor.get_history();
+        assert_eq!(history.get_recent(), vec![HWND(3), HWND(2)]);
+        assert_eq!(history.get_recent_of("C:/Firefox.exe"), vec![HWND(2)]);
+        assert!(executor.take_history_changed());
+        assert!(!executor.take_history_changed());
+
+        // closed windows are forgotten
+        executor.execute(&config, &config.actions[3]).unwrap();
+        executor.refresh(Instant::now());
+        assert_eq!(executor.get_history().get_recent(), vec![HWND(2)]);
+
+        // windows of a previous run are kept only if their handle still belongs to the app
+        let mut saved = WindowHistory::new(8);
+        saved.activate(HWND(1), "C:/code.exe");
+        saved.activate(HWND(2), "C:/other.exe");
+        saved.activate(HWND(9), "C:/gone.exe");
+        executor.set_history(saved);
+        assert_eq!(executor.get_history().get_recent(), vec![HWND(1)]);
+    }
+
+    // the history orders windows where it disagrees with z-order
+    #[test]
+    fn history_order() {
+        let config: Config = serde_json::from_value(json!({
+            "startup": false,
+            "actions": [
+                { "hotkey": "alt + 1", "kind": "focus", "exec": "C:/code.exe" },
+                { "hotkey": "alt + 2", "kind": "focus", "exec": "C:/code.exe", "select": "largest" },
+                { "hotkey": "alt + 3", "kind": "focus", "exec": "C:/notes.exe", "on_active": "return_to_previous" }
+            ]
+        }))
+        .unwrap();
+        // code windows get smaller from 1 to 5, 2 is in front though it is below 1
+        let start = || {
+            let mut backend = FakeBackend::new();
+            for (id, path) in [
+                (1, "C:/code.exe"),
+                (2, "C:/term.exe"),
+                (3, "C:/code.exe"),
+                (4, "C:/notes.exe"),
+                (5, "C:/code.exe"),
+            ] {
+                backend.add_window(id, path);
+                backend.windows[id as usize - 1].rect =
+                    Rect::new(0, 0, 1000 - 100 * id as i32, 500);
+            }
+            backend.foreground = HWND(2);
+            let mut history = WindowHistory::new(8);
+            history.activate(HWND(1), "C:/code.exe");
+            history.activate(HWND(3), "C:/code.exe");
+            history.activate(HWND(5), "C:/code.exe");
+            history.activate(HWND(2), "C:/term.exe");
+            let mut executor = executor(backend);
+            executor.set_history(history);
+            executor
+        };
+
+        // the most recently used window of code, not the top one
+        let mut executor = start();
+        executor.execute(&config, &config.actions[0]).unwrap();
+        assert_eq!(foreground(&executor), 5);
+
+        // a cycle starts in history order whatever the window was selected by
+        let mut executor = start();
+        executor.execute(&config, &config.actions[1]).unwrap();
+        assert_eq!(foreground(&executor), 1);
+        executor.execute(&config, &config.actions[1]).unwrap();
+        assert_eq!(foreground(&executor), 5);
+        executor.execute(&config, &config.actions[1]).unwrap();
+        assert_eq!(foreground(&executor), 3);
+
+        // notes brought to front without ektox returns to the window used before
+        let mut executor = start();
+        executor.backend.focus(HWND(4));
+        executor.execute(&config, &config.actions[2]).unwrap();
+        assert_eq!(foreground(&executor), 2);
+    }
+
+    #[test]
+    fn navigation() {
+        let config: Config = serde_json::from_value(json!({
+            "startup": false,
+            "actions": [
+                { "hotkey": "alt + 1", "kind": "focus", "exec": "C:/slack.exe" },
+                { "hotkey": "alt + 2", "kind": "focus", "exec": "C:/term.exe" },
+                { "hotkey": "alt + leftarrow", "kind": "back" },
+                { "hotkey": "alt + rightarrow", "kind": "forward" }
+            ],
+            "navigation_depth": 3
+        }))
+        .unwrap();
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/code.exe");
+        backend.add_window(2, "C:/slack.exe");
+        backend.add_window(3, "C:/term.exe");
+        backend.foreground = HWND(1);
+        let mut executor = executor(backend);
+        let [slack, term, back, forward] = [0, 1, 2, 3].map(|index| &config.actions[index]);
+
+        // one press returns to where the hotkey jumped from
+        executor.execute(&config, slack).unwrap();
+        executor.execute(&config, back).unwrap();
+        assert_eq!(foreground(&executor), 1);
+        executor.execute(&config, forward).unwrap();
+        assert_eq!(foreground(&executor), 2);
+        assert_eq!(
+            executor.execute(&config, forward).unwrap(),
+            Outcome::NoWindow
+        );
+
+        executor.execute(&config, term).unwrap();
+        executor.execute(&config, back).unwrap();
+        executor.execute(&config, back).unwrap();
+        assert_eq!(foreground(&executor), 1);
+
+        // focusing another window drops the windows ahead
+        executor.execute(&config, term).unwrap();
+        executor.execute(&config, slack).unwrap();
+        executor.execute(&config, back).unwrap();
+        executor.execute(&config, back).unwrap();
+        assert_eq!(foreground(&executor), 1);
+        assert_eq!(executor.execute(&config, back).unwrap(), Outcome::NoWindow);
+        executor.execute(&config, term).unwrap();
+        executor.execute(&config, back).unwrap();
+        assert_eq!(foreground(&executor), 1);
+        assert_eq!(executor.execute(&config, forward).unwrap(), Outcome::Done);
+        assert_eq!(foreground(&executor), 3);
+    }
+
+    #[test]
+    fn marks() {
+        let config: Config = serde_json::from_value(json!({
+            "startup": false,
+            "actions": [
+                { "hotkey": "ctrl + alt + 1", "kind": "mark", "slot": 1 },
+                { "hotkey": "alt + 1", "kind": "jump_to_mark", "slot": 1 },
+                { "hotkey": "alt + 2", "kind": "jump_to_mark", "slot": 2 },
+                { "hotkey": "alt + 3", "kind": "focus", "exec": "C:/code.exe" }
+            ]
+        }))
+        .unwrap();
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/code.exe");
+        backend.add_window(2, "C:/term.exe");
+        backend.foreground = HWND(2);
+        let mut executor = executor(backend);
+        let [mark, jump, empty, code] = [0, 1, 2, 3].map(|index| &config.actions[index]);
+
+        executor.execute(&config, mark).unwrap();
+        assert!(executor.take_marks_changed());
+        executor.execute(&config, code).unwrap();
+        executor.execute(&config, jump).unwrap();
+        assert_eq!(foreground(&executor), 2);
+        assert!(!executor.take_marks_changed());
+        assert_eq!(executor.execute(&config, empty).unwrap(), Outcome::NoWindow);
+
+        // marks of a previous run follow the relaunched app
+        let marks = executor.get_marks().clone();
+        let mut backend = FakeBackend::new();
+        backend.add_window(7, "C:/term.exe");
+        let mut executor = self::executor(backend);
+        executor.set_marks(marks);
+        executor.execute(&config, jump).unwrap();
+        assert_eq!(foreground(&executor), 7);
+        assert!(executor.take_marks_changed());
+        assert_eq!(executor.get_marks().get(1).unwrap().pid, 7);
+    }
+
+    #[test]
+    fn numbered() {
+        let mut config: Config = serde_json::from_value(json!({
+            "startup": false,
+            "apps": { "browser": { "exec": ["C:/chrome.exe"] } },
+            "actions": [{ "hotkey": "win + alt + 9", "kind": "reload" }],
+            "numbered": { "count": 9 }
+        }))
+        .unwrap();
+        config.validate().unwrap();
+        let actions = config.resolve_actions(None).unwrap();
+        assert_eq!(actions.len(), 9);
+        // written actions take precedence
+        assert_eq!(actions[0].kind.get_name(), "reload");
+        let number = |number: usize| {
+            actions
+                .iter()
+                .find(|action| matches!(action.kind, ActionKind::FocusNumber { number: n } if n == number))
+                .unwrap()
+                .clone()
+        };
+
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/term.exe");
+        backend.add_window(2, "C:/code.exe");
+        backend.add_window(3, "C:/term.exe");
+        let spawned = backend.spawned.clone();
+        let mut executor = executor(backend);
+
+        // windows seen at once are numbered from the bottom of z-order, and keep
+        // their number when z-order changes
+        executor.execute(&config, &number(1)).unwrap();
+        assert_eq!(foreground(&executor), 3);
+        executor.execute(&config, &number(3)).unwrap();
+        assert_eq!(foreground(&executor), 1);
+        executor.execute(&config, &number(1)).unwrap();
+        assert_eq!(foreground(&executor), 3);
+        assert_eq!(
+            executor.execute(&config, &number(4)).unwrap(),
+            Outcome::NoWindow
+        );
+
+        // apps share a number, pressing it again cycles through their windows
+        config.numbered = serde_json::from_value(json!({
+            "group": "app",
+            "order": { "pinned": ["browser", "C:/code.exe"] }
+        }))
+        .unwrap();
+        executor.execute(&config, &number(2)).unwrap();
+        assert_eq!(foreground(&executor), 2);
+        executor.execute(&config, &number(3)).unwrap();
+        assert_eq!(foreground(&executor), 3);
+        executor.execute(&config, &number(3)).unwrap();
+        assert_eq!(foreground(&executor), 1);
+        // pinned apps without windows are launched
+        executor.execute(&config, &number(1)).unwrap();
+        assert_eq!(*spawned.borrow(), vec!["C:/chrome.exe"]);
+
+        config.numbered = serde_json::from_value(json!({ "order": "alphabetical" })).unwrap();
+        executor.execute(&config, &number(1)).unwrap();
+        assert_eq!(foreground(&executor), 2);
+    }
+
+    #[test]
+    fn build_command() {
+        let launch: Launch = serde_json::from_value(json!({
+            "args": ["-n"],
+            "cwd": "C:/work",
+            "env": { "LANG": "C" }
+        }))
+        .unwrap();
+        let command = Executor::build_command("C:/code.exe", &launch);
+        assert_eq!(command.get_current_dir(), Some(Path::new("C:/work")));
+        let envs: Vec<_> = command.get_envs().collect();
+        assert_eq!(envs.len(), 1);
+        assert_eq!(envs[0].0, "LANG");
+    }
+
+    #[test]
+    fn toggle() {
+        let config = config();
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/code.exe");
+        backend.add_window(2, "C:/term.exe");
+        let mut executor = executor(backend);
+        executor.execute(&config, &config.actions[2]).unwrap();
+        assert_eq!(executor.get_backend().get_foreground_window(), HWND(2));
+        executor.execute(&config, &config.actions[2]).unwrap();
+        assert!(executor.get_backend().is_minimized(HWND(2)));
+    }
+
+    #[test]
+    fn restore_state() {
+        let config = config();
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/code.exe");
+        backend.add_window(2, "C:/term.exe");
+        backend.set_window_state(HWND(2), WindowState::Snapped);
+        let mut executor = executor(backend);
+        executor.execute(&config, &config.actions[2]).unwrap();
+        executor.execute(&config, &config.actions[2]).unwrap();
+        assert!(executor.get_backend().is_minimized(HWND(2)));
+        executor.execute(&config, &config.actions[2]).unwrap();
+        let backend = executor.get_backend();
+        assert_eq!(backend.get_state(HWND(2)), WindowState::Snapped);
+        assert_eq!(backend.get_foreground_window(), HWND(2));
+    }
+
+    #[test]
+    fn close_foreground() {
+        let config = config();
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/code.exe");
+        backend.add_window(2, "C:/term.exe");
+        backend.foreground = HWND(2);
+        let mut executor = executor(backend);
+        executor.execute(&config, &config.actions[3]).unwrap();
+        assert_eq!(executor.get_backend().get_windows(), vec![HWND(1)]);
+    }
+
+    #[test]
+    fn outcome() {
+        let config = config();
+        let mut executor = executor(FakeBackend::new());
+        assert_eq!(
+            executor.execute(&config, &config.actions[4]).unwrap(),
+            Outcome::Done
+        );
+        assert_eq!(
+            executor.execute(&config, &config.actions[5]).unwrap(),
+            Outcome::SwitchProfile(Some("gaming".to_string()))
+        );
+        assert_eq!(
+            executor.execute(&config, &config.actions[6]).unwrap(),
+            Outcome::NoWindow
+        );
+    }
+}
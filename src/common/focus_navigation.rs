@@ -0,0 +1,131 @@
+use windows::Win32::Foundation::HWND;
+
+// focused windows like the pages of a browser tab, going back and forward
+// walks the stack, focusing another window drops the windows ahead of the
+// current one
+pub struct FocusNavigation {
+    depth: usize,
+    // oldest first
+    windows: Vec<HWND>,
+    current: usize,
+    // window focused by back or forward, its foreground event is not a new visit
+    navigating: Option<HWND>,
+}
+
+impl FocusNavigation {
+    pub fn new(depth: usize) -> Self {
+        FocusNavigation {
+            depth: depth.max(1),
+            windows: Vec::new(),
+            current: 0,
+            navigating: None,
+        }
+    }
+
+    // forget the oldest windows beyond depth
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth.max(1);
+        let excess = self.windows.len().saturating_sub(self.depth);
+        if excess > 0 {
+            self.windows.drain(..excess);
+            self.current = self.current.saturating_sub(excess);
+        }
+    }
+
+    pub fn get_current(&self) -> Option<HWND> {
+        self.windows.get(self.current).copied()
+    }
+
+    pub fn get_windows(&self) -> &Vec<HWND> {
+        &self.windows
+    }
+
+    // window came to foreground
+    pub fn visit(&mut self, hwnd: HWND) {
+        if self.navigating.take() == Some(hwnd) || self.get_current() == Some(hwnd) {
+            return;
+        }
+        if !self.windows.is_empty() {
+            self.windows.truncate(self.current + 1);
+        }
+        self.windows.push(hwnd);
+        self.set_depth(self.depth);
+        self.current = self.windows.len() - 1;
+    }
+
+    // window to focus for going back, if any
+    pub fn back(&mut self) -> Option<HWND> {
+        if self.current == 0 || self.windows.is_empty() {
+            return None;
+        }
+        self.current -= 1;
+        self.navigating = self.get_current();
+        self.navigating
+    }
+
+    pub fn forward(&mut self) -> Option<HWND> {
+        if self.current + 1 >= self.windows.len() {
+            return None;
+        }
+        self.current += 1;
+        self.navigating = self.get_current();
+        self.navigating
+    }
+
+    // window was destroyed, a window left next to itself is kept once
+    pub fn remove(&mut self, hwnd: HWND) {
+        let mut windows: Vec<HWND> = Vec::new();
+        let mut current = 0;
+        for (index, window) in self.windows.iter().enumerate() {
+            if *window != hwnd && windows.last() != Some(window) {
+                windows.push(*window);
+            }
+            if index == self.current {
+                current = windows.len().saturating_sub(1);
+            }
+        }
+        self.windows = windows;
+        self.current = current;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use windows::Win32::Foundation::HWND;
+
+    use super::FocusNavigation;
+
+    #[test]
+    fn it_works() {
+        let mut navigation = FocusNavigation::new(3);
+        assert_eq!(navigation.back(), None);
+        navigation.visit(HWND(1));
+        navigation.visit(HWND(2));
+        navigation.visit(HWND(3));
+        assert_eq!(navigation.back(), Some(HWND(2)));
+        navigation.visit(HWND(2));
+        assert_eq!(navigation.back(), Some(HWND(1)));
+        assert_eq!(navigation.back(), None);
+        assert_eq!(navigation.forward(), Some(HWND(2)));
+        assert_eq!(navigation.forward(), Some(HWND(3)));
+        assert_eq!(navigation.forward(), None);
+
+        // a new visit drops the windows ahead
+        navigation.back();
+        navigation.visit(HWND(2));
+        navigation.visit(HWND(4));
+        assert_eq!(navigation.get_windows(), &vec![HWND(1), HWND(2), HWND(4)]);
+        assert_eq!(navigation.forward(), None);
+
+        // bounded by depth
+        navigation.visit(HWND(5));
+        assert_eq!(navigation.get_windows(), &vec![HWND(2), HWND(4), HWND(5)]);
+        assert_eq!(navigation.get_current(), Some(HWND(5)));
+
+        navigation.remove(HWND(4));
+        assert_eq!(navigation.get_windows(), &vec![HWND(2), HWND(5)]);
+        assert_eq!(navigation.back(), Some(HWND(2)));
+        navigation.remove(HWND(2));
+        assert_eq!(navigation.get_current(), Some(HWND(5)));
+    }
+}
@@ -0,0 +1,261 @@
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::WindowInfo;
+
+use super::{AppError, Query};
+
+// conditions a window must all meet, paths are compared case-insensitively
+// with `/` and `\` treated alike
+// like:
+// { "name": "chrome.exe" }
+// { "path": "C:/Users/*/AppData/Local/Discord/app-*/Discord.exe" }
+// { "path_regex": "jetbrains/.*/bin/idea64\\.exe$", "title": "ektox" }
+// { "class": "ConsoleWindowClass" }
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct MatchRule {
+    // file name of the process image
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    // glob on the full image path, `*` and `?` stay within a directory, `**` crosses them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_regex: Option<String>,
+    // regex on the window title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+}
+
+impl MatchRule {
+    pub fn is_empty(&self) -> bool {
+        self == &MatchRule::default()
+    }
+
+    pub fn compile(&self) -> Result<CompiledRule, AppError> {
+        let regex = |field: &str, pattern: &str, case_insensitive: bool| {
+            RegexBuilder::new(pattern)
+                .case_insensitive(case_insensitive)
+                .build()
+                .map_err(|error| {
+                    AppError::new(format!(
+                        "Match '{}' '{}' Invalid: {}",
+                        field, pattern, error
+                    ))
+                })
+        };
+        Ok(CompiledRule {
+            name: self.name.as_ref().map(|name| name.to_lowercase()),
+            path: match &self.path {
+                Some(glob) => Some(regex("path", &glob_to_regex(glob), true)?),
+                None => None,
+            },
+            path_regex: match &self.path_regex {
+                Some(pattern) => Some(regex("path_regex", pattern, true)?),
+                None => None,
+            },
+            title: match &self.title {
+                Some(pattern) => Some(regex("title", pattern, false)?),
+                None => None,
+            },
+            class: self.class.clone(),
+        })
+    }
+}
+
+pub struct CompiledRule {
+    name: Option<String>,
+    path: Option<Regex>,
+    path_regex: Option<Regex>,
+    title: Option<Regex>,
+    class: Option<String>,
+}
+
+impl CompiledRule {
+    pub fn is_match(&self, window: &WindowInfo) -> bool {
+        let path = normalize_path(window.get_path());
+        if let Some(name) = &self.name {
+            if window.get_basename().to_lowercase() != *name {
+                return false;
+            }
+        }
+        if let Some(glob) = &self.path {
+            if !glob.is_match(&path) {
+                return false;
+            }
+        }
+        if let Some(regex) = &self.path_regex {
+            if !regex.is_match(&path) {
+                return false;
+            }
+        }
+        if let Some(title) = &self.title {
+            if !title.is_match(window.get_title()) {
+                return false;
+            }
+        }
+        if let Some(class) = &self.class {
+            // window classes are registered case-insensitively
+            if !class.eq_ignore_ascii_case(window.get_class()) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+// which windows belong to a target, exact paths, rules or queries, any of them is enough
+#[derive(Default)]
+pub struct WindowMatcher {
+    paths: Vec<String>,
+    rules: Vec<CompiledRule>,
+    queries: Vec<Query>,
+}
+
+impl WindowMatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_path(&mut self, path: &str) {
+        self.paths.push(normalize_path(path).to_lowercase());
+    }
+
+    pub fn add_rule(&mut self, rule: &MatchRule) -> Result<(), AppError> {
+        self.rules.push(rule.compile()?);
+        Ok(())
+    }
+
+    pub fn add_query(&mut self, query: &str) -> Result<(), AppError> {
+        let query = Query::parse(query).map_err(|error| AppError::new(error.to_string()))?;
+        self.queries.push(query);
+        Ok(())
+    }
+
+    pub fn is_match(&self, window: &WindowInfo) -> bool {
+        let path = normalize_path(window.get_path()).to_lowercase();
+        self.paths.contains(&path)
+            || self.rules.iter().any(|rule| rule.is_match(window))
+            || self.queries.iter().any(|query| query.is_match(window))
+    }
+}
+
+pub fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+pub fn is_same_path(left: &str, right: &str) -> bool {
+    normalize_path(left).to_lowercase() == normalize_path(right).to_lowercase()
+}
+
+pub fn glob_to_regex(glob: &str) -> String {
+    let glob = normalize_path(glob);
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use windows::Win32::Foundation::HWND;
+
+    use crate::utils::WindowInfo;
+
+    use super::{is_same_path, MatchRule, WindowMatcher};
+
+    fn window(path: &str, title: &str, class: &str) -> WindowInfo {
+        WindowInfo::new(HWND(1), path, title, class)
+    }
+
+    fn rule(value: serde_json::Value) -> MatchRule {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn it_works() {
+        let chrome = window(
+            "C:/Program Files/Google/Chrome/Application/chrome.exe",
+            "New Tab - Google Chrome",
+            "Chrome_WidgetWin_1",
+        );
+        let rule = rule(json!({ "name": "Chrome.EXE", "class": "chrome_widgetwin_1" }))
+            .compile()
+            .unwrap();
+        assert!(rule.is_match(&chrome));
+        assert!(!rule.is_match(&window("C:/chrome.exe", "", "ConsoleWindowClass")));
+    }
+
+    #[test]
+    fn glob() {
+        let discord = window(
+            "C:\\Users\\alice\\AppData\\Local\\Discord\\app-1.0.9003\\Discord.exe",
+            "Discord",
+            "",
+        );
+        let glob = rule(json!({ "path": "c:/users/*/appdata/local/discord/app-*/discord.exe" }))
+            .compile()
+            .unwrap();
+        assert!(glob.is_match(&discord));
+        let shallow = rule(json!({ "path": "C:/Users/*/Discord.exe" }))
+            .compile()
+            .unwrap();
+        assert!(!shallow.is_match(&discord));
+        let deep = rule(json!({ "path": "C:/Users/**/Discord.exe" }))
+            .compile()
+            .unwrap();
+        assert!(deep.is_match(&discord));
+    }
+
+    #[test]
+    fn regex() {
+        let idea = window(
+            "C:/Program Files/JetBrains/IntelliJ IDEA 2022.1/bin/idea64.exe",
+            "ektox – main.rs",
+            "SunAwtFrame",
+        );
+        let rule = rule(json!({ "path_regex": "jetbrains/.*/idea64\\.exe$", "title": "^ektox" }))
+            .compile()
+            .unwrap();
+        assert!(rule.is_match(&idea));
+        let other = self::rule(json!({ "path_regex": "jetbrains", "title": "^EKTOX" }))
+            .compile()
+            .unwrap();
+        assert!(!other.is_match(&idea));
+        assert!(self::rule(json!({ "title": "(" })).compile().is_err());
+    }
+
+    #[test]
+    fn matcher() {
+        let mut matcher = WindowMatcher::new();
+        matcher.add_path("C:/Program Files/App/App.exe");
+        matcher
+            .add_rule(&rule(json!({ "name": "term.exe" })))
+            .unwrap();
+        assert!(matcher.is_match(&window("c:\\program files\\app\\app.exe", "", "")));
+        assert!(matcher.is_match(&window("D:/tools/term.exe", "", "")));
+        assert!(!matcher.is_match(&window("D:/tools/other.exe", "", "")));
+        assert!(is_same_path("C:\\A\\b.exe", "c:/a/B.EXE"));
+    }
+
+    #[test]
+    fn unknown_field() {
+        let result = serde_json::from_value::<MatchRule>(json!({ "exe": "a.exe" }));
+        assert!(result.is_err());
+    }
+}
@@ -1,9 +1,49 @@
+pub use self::action::{
+    migrate_action_kind, Action, ActionKind, Cycle, FocusOptions, Launch, OnActive, Select, Target,
+};
 pub use self::app::App;
+pub use self::app_alias::AppAlias;
 pub use self::app_error::AppError;
-pub use self::config::{Action, Config};
+pub use self::autostart::{
+    Autostart, AutostartStatus, AutostartStore, MemoryStore, RegistryStore, XdgStore,
+};
+pub use self::config::{Config, Profile, CONFIG_SCHEMA_VERSION};
+pub use self::config_loader::{merge_config, ConfigLayer, ConfigLoader, LayerKind, LoadedFile};
+pub use self::config_migration::{ConfigMigrator, Migration, MigrationReport};
+pub use self::eligibility::Eligibility;
+pub use self::executor::{Executor, Outcome, HISTORY_CAPACITY};
+pub use self::focus_navigation::FocusNavigation;
+pub use self::match_rule::{
+    glob_to_regex, is_same_path, normalize_path, CompiledRule, MatchRule, WindowMatcher,
+};
+pub use self::numbered_bindings::{NumberedBindings, NumberedGroup, NumberedOrder};
+pub use self::state_file::{load_state_file, save_state_file, write_file};
 pub use self::version::Version;
+pub use self::window_history::{HistoryEntry, WindowHistory};
+pub use self::window_marks::{WindowMark, WindowMarks};
+pub use self::window_picker::{PickerKey, PickerStep, TerminalKeys, WindowPicker};
+pub use self::window_query::{Field, ParseQueryError, Query, Term};
+pub use self::window_registry::WindowRegistry;
+pub use self::window_switcher::{SwitcherHit, WindowSwitcher};
 
+mod action;
 mod app;
+mod app_alias;
 mod app_error;
+mod autostart;
 mod config;
+mod config_loader;
+mod config_migration;
+mod eligibility;
+mod executor;
+mod focus_navigation;
+mod match_rule;
+mod numbered_bindings;
+mod state_file;
 mod version;
+mod window_history;
+mod window_marks;
+mod window_picker;
+mod window_query;
+mod window_registry;
+mod window_switcher;
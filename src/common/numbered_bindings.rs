@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utils::Hotkey;
+
+use super::{Action, ActionKind, AppError};
+
+// hotkeys focusing the Nth window or app like the numbers of the taskbar,
+// generated instead of written as actions, the windows are numbered when a
+// hotkey is pressed
+// like:
+// "numbered": { "modifiers": "win + alt", "group": "app", "order": { "pinned": ["browser", "C:/code.exe"] } }
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct NumberedBindings {
+    // combined with the digits 1 to count
+    #[serde(
+        default = "NumberedBindings::default_modifiers",
+        skip_serializing_if = "NumberedBindings::is_default_modifiers"
+    )]
+    pub modifiers: String,
+    #[serde(
+        default = "NumberedBindings::default_count",
+        skip_serializing_if = "NumberedBindings::is_default_count"
+    )]
+    pub count: usize,
+    #[serde(default, skip_serializing_if = "NumberedGroup::is_default")]
+    pub group: NumberedGroup,
+    #[serde(default, skip_serializing_if = "NumberedOrder::is_default")]
+    pub order: NumberedOrder,
+}
+
+// what gets a number
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NumberedGroup {
+    #[default]
+    Window,
+    // windows of the same executable share a number, pressing it again
+    // cycles through them
+    App,
+}
+
+impl NumberedGroup {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+// how numbers are given
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NumberedOrder {
+    // in the order ektox first saw the windows
+    #[default]
+    FirstSeen,
+    // by title for windows, by executable name for apps
+    Alphabetical,
+    // apps defined in `apps` or executable paths first, in this order, the
+    // others follow in first seen order, pinned apps keep their number and
+    // are launched while they have no window if numbering apps
+    Pinned(Vec<String>),
+}
+
+impl NumberedOrder {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl NumberedBindings {
+    fn default_modifiers() -> String {
+        "win + alt".to_string()
+    }
+
+    fn is_default_modifiers(modifiers: &String) -> bool {
+        *modifiers == Self::default_modifiers()
+    }
+
+    fn default_count() -> usize {
+        9
+    }
+
+    fn is_default_count(count: &usize) -> bool {
+        *count == Self::default_count()
+    }
+
+    pub fn get_pinned(&self) -> &[String] {
+        match &self.order {
+            NumberedOrder::Pinned(pinned) => pinned,
+            _ => &[],
+        }
+    }
+
+    pub fn get_pinned_mut(&mut self) -> &mut [String] {
+        match &mut self.order {
+            NumberedOrder::Pinned(pinned) => pinned,
+            _ => &mut [],
+        }
+    }
+
+    // a focus_number action for each number
+    pub fn get_actions(&self) -> Result<Vec<Action>, AppError> {
+        if !(1..=9).contains(&self.count) {
+            return Err(AppError::new(format!(
+                "Numbered bindings must have a count from 1 to 9, not {}",
+                self.count
+            )));
+        }
+        (1..=self.count)
+            .map(|number| {
+                let source = format!("{} + {}", self.modifiers, number);
+                let hotkey = Hotkey::parse(&source).map_err(|error| {
+                    AppError::new(format!(
+                        "Numbered bindings have invalid 'modifiers': {}",
+                        error
+                    ))
+                })?;
+                Ok(Action {
+                    id: None,
+                    hotkey,
+                    kind: ActionKind::FocusNumber { number },
+                })
+            })
+            .collect()
+    }
+}
+
+impl Default for NumberedBindings {
+    fn default() -> Self {
+        NumberedBindings {
+            modifiers: Self::default_modifiers(),
+            count: Self::default_count(),
+            group: NumberedGroup::default(),
+            order: NumberedOrder::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{common::ActionKind, utils::Hotkey};
+
+    use super::{NumberedBindings, NumberedGroup, NumberedOrder};
+
+    #[test]
+    fn it_works() {
+        let numbered: NumberedBindings = serde_json::from_value(json!({
+            "modifiers": "ctrl + alt",
+            "count": 3,
+            "group": "app",
+            "order": { "pinned": ["browser"] }
+        }))
+        .unwrap();
+        assert_eq!(numbered.group, NumberedGroup::App);
+        assert_eq!(numbered.get_pinned(), ["browser"]);
+        let actions = numbered.get_actions().unwrap();
+        assert_eq!(actions.len(), 3);
+        assert_eq!(actions[2].hotkey, Hotkey::parse("ctrl + alt + 3").unwrap());
+        assert!(matches!(
+            actions[2].kind,
+            ActionKind::FocusNumber { number: 3 }
+        ));
+
+        let numbered: NumberedBindings = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(numbered, NumberedBindings::default());
+        assert_eq!(numbered.order, NumberedOrder::FirstSeen);
+        assert_eq!(numbered.get_actions().unwrap().len(), 9);
+        assert_eq!(serde_json::to_value(&numbered).unwrap(), json!({}));
+
+        let numbered = NumberedBindings {
+            count: 10,
+            ..Default::default()
+        };
+        assert!(numbered.get_actions().is_err());
+        let numbered = NumberedBindings {
+            modifiers: "hyper".to_string(),
+            ..Default::default()
+        };
+        assert!(numbered.get_actions().is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use std::{fs, path::Path};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::AppError;
+
+// json files ektox keeps its own state in, like the window history, a missing
+// or unreadable file is no state
+pub fn load_state_file<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+// name describes the file in errors, like "Window History"
+pub fn save_state_file<T: Serialize>(path: &Path, name: &str, value: &T) -> Result<(), AppError> {
+    let content = serde_json::to_string(value).map_err(|error| AppError::new(error.to_string()))?;
+    write_file(path, name, &content)
+}
+
+// write a file, creating its directory first
+pub fn write_file(path: &Path, name: &str, content: &str) -> Result<(), AppError> {
+    if let Some(directory) = path.parent() {
+        let _ = fs::create_dir_all(directory);
+    }
+    fs::write(path, content).map_err(|error| {
+        AppError::new(format!(
+            "Write {} '{}' Failed: {}",
+            name,
+            path.display(),
+            error
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, env, fs};
+
+    use super::{load_state_file, save_state_file};
+
+    #[test]
+    fn it_works() {
+        let directory = env::temp_dir().join(format!("ektox-state-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        let path = directory.join("nested").join("test.state");
+        assert_eq!(load_state_file::<bool>(&path), None);
+
+        let mut state = BTreeMap::new();
+        state.insert("a".to_string(), 1);
+        save_state_file(&path, "Test State", &state).unwrap();
+        assert_eq!(load_state_file(&path), Some(state));
+
+        fs::write(&path, "{").unwrap();
+        assert_eq!(load_state_file::<BTreeMap<String, i32>>(&path), None);
+        let _ = fs::remove_dir_all(&directory);
+    }
+}
@@ -1,6 +1,10 @@
 use std::fmt;
 
-#[derive(Eq, Ord, Clone, Debug)]
+use serde::de::Visitor;
+
+use super::AppError;
+
+#[derive(Eq, Clone, Debug)]
 pub struct Version {
     major: i32,
     minor: i32,
@@ -11,16 +15,30 @@ pub struct Version {
 impl Version {
     #[allow(unused)]
     pub fn new(version: &'static str) -> Self {
-        let mut version_parts = version.split('.');
-        let major = version_parts.next().unwrap().parse().unwrap();
-        let minor = version_parts.next().unwrap().parse().unwrap();
-        let patch = version_parts.next().unwrap().parse().unwrap();
-        Version {
+        Self::parse(version).unwrap()
+    }
+
+    pub fn parse(version: &str) -> Result<Self, AppError> {
+        let invalid = || AppError::new(format!("Invalid version '{}'", version));
+        let mut version_parts = version.trim().split('.');
+        let mut next_part = || -> Result<i32, AppError> {
+            match version_parts.next().map(|part| part.parse::<i32>()) {
+                Some(Ok(part)) if part >= 0 => Ok(part),
+                _ => Err(invalid()),
+            }
+        };
+        let major = next_part()?;
+        let minor = next_part()?;
+        let patch = next_part()?;
+        if version_parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(Version {
             major,
             minor,
             patch,
             version: format!("{}.{}.{}", major, minor, patch),
-        }
+        })
     }
     #[allow(unused)]
     pub fn from_cargo_package() -> Self {
@@ -63,23 +81,52 @@ impl PartialEq for Version {
 
 impl PartialOrd for Version {
     fn partial_cmp(&self, other: &Version) -> Option<std::cmp::Ordering> {
-        let mut result = std::cmp::Ordering::Equal;
-        loop {
-            if self.major != other.major {
-                result = self.major.cmp(&other.major);
-                break;
-            }
-            if self.minor != other.minor {
-                result = self.minor.cmp(&other.minor);
-                break;
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.major
+            .cmp(&other.major)
+            .then(self.minor.cmp(&other.minor))
+            .then(self.patch.cmp(&other.patch))
+    }
+}
+
+impl serde::Serialize for Version {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_str())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Version {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct VersionVisitor;
+        impl<'de> Visitor<'de> for VersionVisitor {
+            type Value = Version;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("expect version string like '1.0.0'")
             }
-            if self.patch != other.patch {
-                result = self.patch.cmp(&other.patch);
-                break;
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                match Version::parse(v) {
+                    Ok(version) => Ok(version),
+                    Err(err) => Err(serde::de::Error::custom(err.to_string())),
+                }
             }
-            break;
         }
-        Some(result)
+        deserializer.deserialize_str(VersionVisitor)
     }
 }
 
@@ -117,4 +164,21 @@ mod tests {
         let new_version = Version::new("1.1.1");
         assert!(new_version == old_version);
     }
+
+    #[test]
+    fn parse() {
+        assert_eq!(Version::parse(" 1.2.3 ").unwrap(), Version::new("1.2.3"));
+        assert!(Version::parse("1.2").is_err());
+        assert!(Version::parse("1.2.3.4").is_err());
+        assert!(Version::parse("1.a.3").is_err());
+        assert!(Version::parse("1.-2.3").is_err());
+    }
+
+    #[test]
+    fn serde() {
+        let version: Version = serde_json::from_str("\"2.0.1\"").unwrap();
+        assert_eq!(version, Version::new("2.0.1"));
+        assert_eq!(serde_json::to_string(&version).unwrap(), "\"2.0.1\"");
+        assert!(serde_json::from_str::<Version>("\"2.0\"").is_err());
+    }
 }
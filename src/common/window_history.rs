@@ -0,0 +1,185 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+use windows::Win32::Foundation::HWND;
+
+use super::{load_state_file, normalize_path, save_state_file, AppError};
+
+// a window brought to foreground, known by its handle so that it
+// survives title changes
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub hwnd: isize,
+    // image path of the process, windows of the same path belong to the same app
+    pub path: String,
+}
+
+impl HistoryEntry {
+    pub fn get_hwnd(&self) -> HWND {
+        HWND(self.hwnd)
+    }
+
+    pub fn get_path(&self) -> &String {
+        &self.path
+    }
+}
+
+// windows in the order they were last activated, most recent first, globally
+// and per app, each list keeps at most capacity windows
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WindowHistory {
+    capacity: usize,
+    recent: VecDeque<HistoryEntry>,
+    // by lowercase image path
+    apps: BTreeMap<String, VecDeque<isize>>,
+}
+
+impl WindowHistory {
+    pub fn new(capacity: usize) -> Self {
+        WindowHistory {
+            capacity: capacity.max(1),
+            recent: VecDeque::new(),
+            apps: BTreeMap::new(),
+        }
+    }
+
+    fn app_key(path: &str) -> String {
+        normalize_path(path).to_lowercase()
+    }
+
+    // window came to foreground
+    pub fn activate(&mut self, hwnd: HWND, path: &str) {
+        self.remove(hwnd);
+        self.recent.push_front(HistoryEntry {
+            hwnd: hwnd.0,
+            path: path.to_string(),
+        });
+        self.recent.truncate(self.capacity);
+        let app = self.apps.entry(Self::app_key(path)).or_default();
+        app.push_front(hwnd.0);
+        app.truncate(self.capacity);
+    }
+
+    // window was destroyed
+    pub fn remove(&mut self, hwnd: HWND) {
+        self.recent.retain(|entry| entry.hwnd != hwnd.0);
+        for windows in self.apps.values_mut() {
+            windows.retain(|window| *window != hwnd.0);
+        }
+        self.apps.retain(|_, windows| !windows.is_empty());
+    }
+
+    // keep only windows which still exist, handles may have been reused by
+    // windows of another app
+    pub fn retain<F: Fn(&HistoryEntry) -> bool>(&mut self, exists: F) {
+        let app_entries = self.apps.iter().flat_map(|(path, windows)| {
+            windows.iter().map(|hwnd| HistoryEntry {
+                hwnd: *hwnd,
+                path: path.clone(),
+            })
+        });
+        let gone: Vec<HWND> = self
+            .recent
+            .iter()
+            .cloned()
+            .chain(app_entries)
+            .filter(|entry| !exists(entry))
+            .map(|entry| entry.get_hwnd())
+            .collect();
+        for hwnd in gone {
+            self.remove(hwnd);
+        }
+    }
+
+    pub fn get_entries(&self) -> &VecDeque<HistoryEntry> {
+        &self.recent
+    }
+
+    // most recently used windows, globally
+    pub fn get_recent(&self) -> Vec<HWND> {
+        self.recent.iter().map(HistoryEntry::get_hwnd).collect()
+    }
+
+    // most recently used windows of the app with this image path
+    pub fn get_recent_of(&self, path: &str) -> Vec<HWND> {
+        self.apps
+            .get(&Self::app_key(path))
+            .map(|windows| windows.iter().map(|hwnd| HWND(*hwnd)).collect())
+            .unwrap_or_default()
+    }
+
+    // 0 for the most recent window
+    pub fn get_rank(&self, hwnd: HWND) -> Option<usize> {
+        self.recent.iter().position(|entry| entry.hwnd == hwnd.0)
+    }
+
+    // window used before the given one, like the foreground window
+    pub fn get_previous(&self, hwnd: HWND) -> Option<HWND> {
+        self.recent
+            .iter()
+            .map(HistoryEntry::get_hwnd)
+            .find(|window| *window != hwnd)
+    }
+
+    pub fn load(path: &Path, capacity: usize) -> Self {
+        load_state_file::<WindowHistory>(path)
+            .map(|mut history| {
+                history.capacity = capacity.max(1);
+                history
+            })
+            .unwrap_or_else(|| WindowHistory::new(capacity))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), AppError> {
+        save_state_file(path, "Window History", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use windows::Win32::Foundation::HWND;
+
+    use super::WindowHistory;
+
+    #[test]
+    fn it_works() {
+        let mut history = WindowHistory::new(3);
+        history.activate(HWND(1), "C:/code.exe");
+        history.activate(HWND(2), "C:/term.exe");
+        history.activate(HWND(3), "C:\\Code.exe");
+        history.activate(HWND(1), "C:/code.exe");
+        assert_eq!(history.get_recent(), vec![HWND(1), HWND(3), HWND(2)]);
+        assert_eq!(history.get_recent_of("c:/code.exe"), vec![HWND(1), HWND(3)]);
+        assert_eq!(history.get_previous(HWND(1)), Some(HWND(3)));
+        assert_eq!(history.get_rank(HWND(2)), Some(2));
+
+        // bounded, the oldest window is forgotten
+        history.activate(HWND(4), "C:/chrome.exe");
+        assert_eq!(history.get_recent(), vec![HWND(4), HWND(1), HWND(3)]);
+        assert_eq!(history.get_recent_of("C:/term.exe"), vec![HWND(2)]);
+
+        history.remove(HWND(1));
+        assert_eq!(history.get_recent(), vec![HWND(4), HWND(3)]);
+        assert_eq!(history.get_recent_of("C:/code.exe"), vec![HWND(3)]);
+        history.retain(|entry| entry.get_hwnd() != HWND(2));
+        assert!(history.get_recent_of("C:/term.exe").is_empty());
+    }
+
+    #[test]
+    fn persist() {
+        let path = env::temp_dir().join(format!("ektox-history-{}.state", std::process::id()));
+        let mut history = WindowHistory::new(8);
+        history.activate(HWND(1), "C:/code.exe");
+        history.activate(HWND(2), "C:/term.exe");
+        history.save(&path).unwrap();
+        let loaded = WindowHistory::load(&path, 8);
+        assert_eq!(loaded, history);
+        fs::remove_file(&path).unwrap();
+        assert!(WindowHistory::load(&path, 8).get_recent().is_empty());
+    }
+}
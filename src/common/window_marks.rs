@@ -0,0 +1,163 @@
+use std::{collections::BTreeMap, path::Path};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use windows::Win32::Foundation::HWND;
+
+use crate::utils::{WindowInfo, WindowSnapshot};
+
+use super::{is_same_path, load_state_file, save_state_file, AppError};
+
+// a window put into a slot, identified by more than its handle so that it
+// can be found again once the app was started again
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WindowMark {
+    pub hwnd: isize,
+    pub pid: u32,
+    pub path: String,
+    pub class: String,
+    // regex, windows with a matching title are preferred when the marked
+    // window is gone
+    pub title: String,
+}
+
+impl WindowMark {
+    // title pattern is the whole title of window if not given
+    pub fn new(window: &WindowInfo, title: Option<&String>) -> Self {
+        let title = match title {
+            Some(pattern) => pattern.clone(),
+            None => format!("^{}$", regex::escape(window.get_title())),
+        };
+        WindowMark {
+            hwnd: window.get_hwnd().0,
+            pid: window.get_pid(),
+            path: window.get_path().clone(),
+            class: window.get_class().clone(),
+            title,
+        }
+    }
+
+    pub fn get_hwnd(&self) -> HWND {
+        HWND(self.hwnd)
+    }
+
+    // the marked window itself, its handle is not reused by another process
+    // while the process lives
+    pub fn is_window(&self, window: &WindowInfo) -> bool {
+        window.get_hwnd().0 == self.hwnd && window.get_pid() == self.pid
+    }
+
+    // a window of the same app which can take the place of the marked one
+    pub fn is_replacement(&self, window: &WindowInfo) -> bool {
+        is_same_path(window.get_path(), &self.path) && window.get_class() == &self.class
+    }
+}
+
+// slots of marked windows
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct WindowMarks {
+    marks: BTreeMap<u32, WindowMark>,
+}
+
+impl WindowMarks {
+    pub fn new() -> Self {
+        WindowMarks::default()
+    }
+
+    pub fn set(&mut self, slot: u32, mark: WindowMark) {
+        self.marks.insert(slot, mark);
+    }
+
+    pub fn get(&self, slot: u32) -> Option<&WindowMark> {
+        self.marks.get(&slot)
+    }
+
+    pub fn remove(&mut self, slot: u32) -> Option<WindowMark> {
+        self.marks.remove(&slot)
+    }
+
+    pub fn len(&self) -> usize {
+        self.marks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.marks.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&u32, &WindowMark)> {
+        self.marks.iter()
+    }
+
+    // window of slot among windows, the marked window if it still exists,
+    // otherwise a window of the same app preferring one with a matching title
+    // and one of the same process, the mark follows the window found
+    pub fn resolve(&mut self, slot: u32, windows: &WindowSnapshot) -> Option<HWND> {
+        let mark = self.marks.get_mut(&slot)?;
+        if windows.iter().any(|window| mark.is_window(window)) {
+            return Some(mark.get_hwnd());
+        }
+        // checked when marked
+        let title = Regex::new(&mark.title).ok();
+        let window = windows
+            .iter()
+            .filter(|window| mark.is_replacement(window))
+            .min_by_key(|window| {
+                (
+                    !title
+                        .as_ref()
+                        .is_some_and(|title| title.is_match(window.get_title())),
+                    window.get_pid() != mark.pid,
+                )
+            })?;
+        mark.hwnd = window.get_hwnd().0;
+        mark.pid = window.get_pid();
+        Some(mark.get_hwnd())
+    }
+
+    pub fn load(path: &Path) -> Self {
+        load_state_file(path).unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), AppError> {
+        save_state_file(path, "Window Marks", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use windows::Win32::Foundation::HWND;
+
+    use crate::utils::{FakeBackend, WindowBackend};
+
+    use super::{WindowMark, WindowMarks};
+
+    #[test]
+    fn it_works() {
+        let mut backend = FakeBackend::new();
+        let code = backend.add_window(1, "C:/code.exe");
+        backend.set_window_title(code, "main.rs - ektox", "");
+        let mut marks = WindowMarks::new();
+        marks.set(1, WindowMark::new(&backend.describe(code), None));
+        assert_eq!(marks.resolve(1, &backend.snapshot()), Some(code));
+        assert_eq!(marks.resolve(2, &backend.snapshot()), None);
+
+        // relaunched, the window with the marked title is preferred
+        backend.close(code);
+        let other = backend.add_window(5, "C:/code.exe");
+        backend.set_window_title(other, "notes - ektox", "");
+        let again = backend.add_window(6, "C:/Code.exe");
+        backend.set_window_title(again, "main.rs - ektox", "");
+        assert_eq!(marks.resolve(1, &backend.snapshot()), Some(again));
+        assert_eq!(marks.get(1).unwrap().pid, 6);
+
+        // any window of the app if none has the title
+        let pattern = "^.* - ektox$".to_string();
+        marks.set(2, WindowMark::new(&backend.describe(other), Some(&pattern)));
+        backend.close(other);
+        backend.set_window_title(again, "main.rs", "");
+        assert_eq!(marks.resolve(2, &backend.snapshot()), Some(HWND(6)));
+        backend.close(again);
+        assert_eq!(marks.resolve(2, &backend.snapshot()), None);
+    }
+}
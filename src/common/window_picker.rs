@@ -0,0 +1,422 @@
+use std::{
+    io::{self, Write},
+    time::Duration,
+};
+
+use crossterm::{
+    cursor::MoveTo,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use windows::Win32::Foundation::HWND;
+
+use crate::utils::WindowBackend;
+
+use super::{AppError, Config, SwitcherHit, WindowHistory, WindowSwitcher};
+
+// windows are listed again this often while no key is pressed, so that the
+// list stays live
+const TICK: Duration = Duration::from_millis(500);
+
+// what a key press does in the picker, read from the terminal or scripted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickerKey {
+    Char(char),
+    Backspace,
+    Up,
+    Down,
+    // focus the selected window
+    Enter,
+    // close the selected window
+    Close,
+    Cancel,
+    // no key was pressed for a while
+    Tick,
+}
+
+impl PickerKey {
+    pub fn from_event(event: &KeyEvent) -> Option<Self> {
+        let ctrl = event.modifiers.contains(KeyModifiers::CONTROL);
+        match event.code {
+            KeyCode::Char('k') if ctrl => Some(PickerKey::Close),
+            KeyCode::Char('c') if ctrl => Some(PickerKey::Cancel),
+            KeyCode::Char('p') if ctrl => Some(PickerKey::Up),
+            KeyCode::Char('n') if ctrl => Some(PickerKey::Down),
+            KeyCode::Char(_) if ctrl => None,
+            KeyCode::Char(c) => Some(PickerKey::Char(c)),
+            KeyCode::Backspace => Some(PickerKey::Backspace),
+            KeyCode::Up => Some(PickerKey::Up),
+            KeyCode::Down => Some(PickerKey::Down),
+            KeyCode::Enter => Some(PickerKey::Enter),
+            KeyCode::Esc => Some(PickerKey::Cancel),
+            _ => None,
+        }
+    }
+}
+
+// what the picker does after a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickerStep {
+    Continue,
+    Focused(HWND),
+    Cancelled,
+}
+
+// keys pressed in the terminal, a tick when none was pressed in time
+pub struct TerminalKeys;
+
+impl Iterator for TerminalKeys {
+    type Item = PickerKey;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if !event::poll(TICK).ok()? {
+                return Some(PickerKey::Tick);
+            }
+            match event::read().ok()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => {
+                    if let Some(key) = PickerKey::from_event(&key) {
+                        return Some(key);
+                    }
+                }
+                Event::Resize(_, _) => return Some(PickerKey::Tick),
+                _ => {}
+            }
+        }
+    }
+}
+
+// live list of windows filtered by what is typed, searched by the window switcher,
+// windows are listed on ticks and after closing one, typing only searches again
+pub struct WindowPicker {
+    query: String,
+    selected: usize,
+    hits: Vec<SwitcherHit>,
+    // windows of the last listing
+    switcher: Option<WindowSwitcher>,
+    config: Option<Config>,
+    history: Option<WindowHistory>,
+    // the window the picker itself runs in
+    excluded: Option<HWND>,
+}
+
+impl WindowPicker {
+    pub fn new() -> Self {
+        WindowPicker {
+            query: String::new(),
+            selected: 0,
+            hits: Vec::new(),
+            switcher: None,
+            config: None,
+            history: None,
+            excluded: None,
+        }
+    }
+
+    // windows of apps defined in configure are found by their name
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    pub fn with_history(mut self, history: WindowHistory) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    // leave a window out of the list, like the console of the picker
+    pub fn excluding(mut self, hwnd: HWND) -> Self {
+        self.excluded = Some(hwnd);
+        self
+    }
+
+    pub fn get_query(&self) -> &String {
+        &self.query
+    }
+
+    pub fn get_hits(&self) -> &Vec<SwitcherHit> {
+        &self.hits
+    }
+
+    pub fn get_selected(&self) -> Option<&SwitcherHit> {
+        self.hits.get(self.selected)
+    }
+
+    // list windows again and search them
+    pub fn refresh(&mut self, backend: &dyn WindowBackend) {
+        let excluded = self.excluded;
+        let snapshot = backend
+            .snapshot()
+            .filter(|window| Some(window.get_hwnd()) != excluded);
+        let mut switcher = WindowSwitcher::new(snapshot);
+        if let Some(config) = &self.config {
+            switcher = switcher.with_apps(config);
+        }
+        if let Some(history) = &self.history {
+            switcher = switcher.with_history(history);
+        }
+        self.switcher = Some(switcher);
+        self.search();
+    }
+
+    // search the last listing for the query, the selected window stays
+    // selected if it still matches
+    fn search(&mut self) {
+        let selected = self.get_selected().map(|hit| hit.get_window().get_hwnd());
+        self.hits = self
+            .switcher
+            .as_ref()
+            .map(|switcher| switcher.search(&self.query))
+            .unwrap_or_default();
+        self.selected = self
+            .hits
+            .iter()
+            .position(|hit| Some(hit.get_window().get_hwnd()) == selected)
+            .unwrap_or_else(|| self.selected.min(self.hits.len().saturating_sub(1)));
+    }
+
+    pub fn handle(&mut self, backend: &mut dyn WindowBackend, key: PickerKey) -> PickerStep {
+        match key {
+            PickerKey::Char(c) => {
+                self.query.push(c);
+                self.selected = 0;
+                self.hits.clear();
+                self.search();
+            }
+            PickerKey::Backspace => {
+                self.query.pop();
+                self.selected = 0;
+                self.hits.clear();
+                self.search();
+            }
+            PickerKey::Up => self.selected = self.selected.saturating_sub(1),
+            PickerKey::Down => {
+                self.selected = (self.selected + 1).min(self.hits.len().saturating_sub(1))
+            }
+            PickerKey::Enter => {
+                if let Some(hwnd) = self.get_selected().map(|hit| hit.get_window().get_hwnd()) {
+                    backend.focus(hwnd);
+                    return PickerStep::Focused(hwnd);
+                }
+            }
+            PickerKey::Close => {
+                if let Some(hwnd) = self.get_selected().map(|hit| hit.get_window().get_hwnd()) {
+                    backend.close(hwnd);
+                    self.refresh(backend);
+                }
+            }
+            PickerKey::Cancel => return PickerStep::Cancelled,
+            PickerKey::Tick => self.refresh(backend),
+        }
+        PickerStep::Continue
+    }
+
+    // query on the first line, windows below with the characters matched by
+    // the query in bold, the selected window reversed
+    pub fn render(&self, out: &mut impl Write, width: u16, height: u16) -> io::Result<()> {
+        let width = width as usize;
+        let rows = (height as usize).saturating_sub(1);
+        // scroll so that the selected window is visible
+        let first = (self.selected + 1).saturating_sub(rows);
+        queue!(out, MoveTo(0, 0), Clear(ClearType::All))?;
+        queue!(out, Print(format!("> {}", self.query)))?;
+        for (row, hit) in self.hits.iter().enumerate().skip(first).take(rows) {
+            queue!(out, MoveTo(0, (row - first + 1) as u16))?;
+            if row == self.selected {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+            let title = hit.get_window().get_title();
+            let app = hit.get_app();
+            let fields = [
+                (title.as_str(), &hit.title_positions),
+                (" - ", &Vec::new()),
+                (app.as_str(), &hit.app_positions),
+            ];
+            let mut printed = 0;
+            for (text, positions) in fields {
+                for (index, c) in text.chars().enumerate() {
+                    if printed >= width {
+                        break;
+                    }
+                    if positions.contains(&index) {
+                        queue!(
+                            out,
+                            SetAttribute(Attribute::Bold),
+                            Print(c),
+                            SetAttribute(Attribute::NormalIntensity)
+                        )?;
+                    } else {
+                        queue!(out, Print(c))?;
+                    }
+                    printed += 1;
+                }
+            }
+            queue!(out, SetAttribute(Attribute::Reset))?;
+        }
+        queue!(out, MoveTo((self.query.chars().count() + 2) as u16, 0))?;
+        out.flush()
+    }
+
+    // handle keys until a window is focused, the picker is cancelled or keys
+    // run out, rendering after each one, size gives columns and rows
+    pub fn run(
+        &mut self,
+        backend: &mut dyn WindowBackend,
+        keys: impl IntoIterator<Item = PickerKey>,
+        out: &mut impl Write,
+        size: impl Fn() -> (u16, u16),
+    ) -> io::Result<Option<HWND>> {
+        self.refresh(backend);
+        let (width, height) = size();
+        self.render(out, width, height)?;
+        for key in keys {
+            match self.handle(backend, key) {
+                PickerStep::Focused(hwnd) => return Ok(Some(hwnd)),
+                PickerStep::Cancelled => return Ok(None),
+                PickerStep::Continue => {}
+            }
+            let (width, height) = size();
+            self.render(out, width, height)?;
+        }
+        Ok(None)
+    }
+
+    // run in the terminal of the process, which is restored afterwards
+    pub fn run_in_terminal(
+        &mut self,
+        backend: &mut dyn WindowBackend,
+    ) -> Result<Option<HWND>, AppError> {
+        let error = |error: io::Error| AppError::new(format!("Terminal Error: {}", error));
+        let mut out = io::stdout();
+        terminal::enable_raw_mode().map_err(error)?;
+        execute!(out, EnterAlternateScreen).map_err(error)?;
+        let size = || terminal::size().unwrap_or((80, 24));
+        let result = self.run(backend, TerminalKeys, &mut out, size);
+        let restored =
+            execute!(out, LeaveAlternateScreen).and_then(|_| terminal::disable_raw_mode());
+        let window = result.map_err(error)?;
+        restored.map_err(error)?;
+        Ok(window)
+    }
+}
+
+impl Default for WindowPicker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use windows::Win32::Foundation::HWND;
+
+    use crate::utils::{FakeBackend, WindowBackend};
+
+    use super::{PickerKey, WindowPicker};
+
+    fn backend() -> FakeBackend {
+        let mut backend = FakeBackend::new();
+        let code = backend.add_window(1, "C:/code.exe");
+        backend.set_window_title(code, "main.rs - ektox", "");
+        let slack = backend.add_window(2, "C:/slack.exe");
+        backend.set_window_title(slack, "general - Slack", "");
+        let term = backend.add_window(3, "C:/term.exe");
+        backend.set_window_title(term, "ektox", "");
+        backend
+    }
+
+    fn keys(typed: &str) -> Vec<PickerKey> {
+        typed.chars().map(PickerKey::Char).collect()
+    }
+
+    #[test]
+    fn it_works() {
+        let mut backend = backend();
+        let mut picker = WindowPicker::new();
+        let mut out: Vec<u8> = Vec::new();
+        let mut script = keys("ektox");
+        // the selection stops at the last window
+        script.extend([
+            PickerKey::Down,
+            PickerKey::Down,
+            PickerKey::Up,
+            PickerKey::Down,
+        ]);
+        script.push(PickerKey::Enter);
+        let window = picker
+            .run(&mut backend, script, &mut out, || (80, 24))
+            .unwrap();
+        assert_eq!(window, Some(HWND(1)));
+        assert_eq!(backend.get_foreground_window(), HWND(1));
+        assert_eq!(picker.get_query(), "ektox");
+        assert_eq!(picker.get_hits().len(), 2);
+        // unmatched characters are printed as they are
+        assert!(String::from_utf8_lossy(&out).contains("main.rs - "));
+
+        // typing narrows the list, backspace widens it again
+        let mut picker = WindowPicker::new();
+        let mut script = keys("slz");
+        script.push(PickerKey::Backspace);
+        script.push(PickerKey::Cancel);
+        let window = picker
+            .run(&mut backend, script, &mut out, || (80, 24))
+            .unwrap();
+        assert_eq!(window, None);
+        assert_eq!(picker.get_hits().len(), 1);
+    }
+
+    #[test]
+    fn close() {
+        let mut backend = backend();
+        let mut picker = WindowPicker::new();
+        let mut out: Vec<u8> = Vec::new();
+        let mut script = keys("ektox");
+        script.extend([PickerKey::Close, PickerKey::Tick]);
+        picker
+            .run(&mut backend, script, &mut out, || (80, 2))
+            .unwrap();
+        assert_eq!(backend.closed, vec![HWND(3)]);
+        assert_eq!(picker.get_hits().len(), 1);
+        assert_eq!(
+            picker.get_selected().unwrap().get_window().get_hwnd(),
+            HWND(1)
+        );
+    }
+
+    #[test]
+    fn listing() {
+        let mut backend = backend();
+        let mut picker = WindowPicker::new().excluding(HWND(2));
+        picker.refresh(&backend);
+        assert_eq!(picker.get_hits().len(), 2);
+
+        // typing searches the last listing, a tick lists windows again
+        let code = backend.add_window(4, "C:/code.exe");
+        backend.set_window_title(code, "lib.rs - ektox", "");
+        picker.handle(&mut backend, PickerKey::Char('e'));
+        assert_eq!(picker.get_hits().len(), 2);
+        picker.handle(&mut backend, PickerKey::Tick);
+        assert_eq!(picker.get_hits().len(), 3);
+    }
+
+    #[test]
+    fn from_event() {
+        let key = |code, modifiers| PickerKey::from_event(&KeyEvent::new(code, modifiers));
+        assert_eq!(
+            key(KeyCode::Char('k'), KeyModifiers::CONTROL),
+            Some(PickerKey::Close)
+        );
+        assert_eq!(
+            key(KeyCode::Char('k'), KeyModifiers::NONE),
+            Some(PickerKey::Char('k'))
+        );
+        assert_eq!(
+            key(KeyCode::Esc, KeyModifiers::NONE),
+            Some(PickerKey::Cancel)
+        );
+        assert_eq!(key(KeyCode::Char('x'), KeyModifiers::CONTROL), None);
+    }
+}
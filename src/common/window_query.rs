@@ -0,0 +1,543 @@
+use std::fmt::Display;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::utils::{WindowBackend, WindowInfo, WindowSnapshot};
+
+use super::{glob_to_regex, is_same_path, normalize_path};
+
+// compact window query, terms next to each other must all match
+// like:
+// exe:code.exe title~"ektox" !class:ConsoleWindowClass monitor:2
+// (exe:chrome.exe | exe:firefox.exe) not title:private
+//
+// fields:
+// exe:NAME   process file name      exe~REGEX    regex on the file name
+// path:GLOB  glob on the image path path~REGEX   regex on the image path
+// title:TEXT title contains text    title~REGEX  regex on the title
+// class:NAME window class           class~REGEX  regex on the window class
+// monitor:N  window is on the nth monitor
+// everything but title regex is case-insensitive
+#[derive(Debug)]
+pub enum Query {
+    And(Vec<Query>),
+    Or(Vec<Query>),
+    Not(Box<Query>),
+    Term(Term),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Exe,
+    Path,
+    Title,
+    Class,
+    Monitor,
+}
+
+#[derive(Debug)]
+pub enum Term {
+    Exe(String),
+    Path(Regex, String),
+    Title(String),
+    Class(String),
+    Monitor(u32),
+    // regex on a field with its source
+    Regex(Field, Regex, String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseQueryError {
+    source: String,
+    position: usize,
+    message: String,
+}
+
+impl ParseQueryError {
+    fn new(source: &str, position: usize, message: &str) -> Self {
+        ParseQueryError {
+            source: source.to_string(),
+            position,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn get_position(&self) -> usize {
+        self.position
+    }
+}
+
+impl Display for ParseQueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ParseQueryError: {} at {} of '{}'",
+            self.message, self.position, self.source
+        )
+    }
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "exe" => Some(Field::Exe),
+            "path" => Some(Field::Path),
+            "title" => Some(Field::Title),
+            "class" => Some(Field::Class),
+            "monitor" => Some(Field::Monitor),
+            _ => None,
+        }
+    }
+
+    fn get_name(&self) -> &'static str {
+        match self {
+            Field::Exe => "exe",
+            Field::Path => "path",
+            Field::Title => "title",
+            Field::Class => "class",
+            Field::Monitor => "monitor",
+        }
+    }
+
+    fn get_value(&self, window: &WindowInfo) -> String {
+        match self {
+            Field::Exe => window.get_basename().to_string(),
+            Field::Path => normalize_path(window.get_path()),
+            Field::Title => window.get_title().clone(),
+            Field::Class => window.get_class().clone(),
+            Field::Monitor => window.get_monitor().to_string(),
+        }
+    }
+}
+
+impl Query {
+    pub fn parse(source: &str) -> Result<Self, ParseQueryError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            source,
+            tokens,
+            index: 0,
+        };
+        let query = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(query),
+            Some((position, _)) => Err(ParseQueryError::new(source, *position, "unexpected ')'")),
+        }
+    }
+
+    // windows matching the query in z-order
+    pub fn find(&self, backend: &dyn WindowBackend) -> WindowSnapshot {
+        backend.snapshot().filter(|window| self.is_match(window))
+    }
+
+    pub fn is_match(&self, window: &WindowInfo) -> bool {
+        match self {
+            Query::And(queries) => queries.iter().all(|query| query.is_match(window)),
+            Query::Or(queries) => queries.iter().any(|query| query.is_match(window)),
+            Query::Not(query) => !query.is_match(window),
+            Query::Term(term) => term.is_match(window),
+        }
+    }
+}
+
+impl Term {
+    fn new(field: Field, regex: bool, value: String) -> Result<Self, String> {
+        if regex {
+            if field == Field::Monitor {
+                return Err("monitor does not support regex".to_string());
+            }
+            let regex = RegexBuilder::new(&value)
+                .case_insensitive(field != Field::Title)
+                .build()
+                .map_err(|error| error.to_string())?;
+            return Ok(Term::Regex(field, regex, value));
+        }
+        match field {
+            Field::Exe => Ok(Term::Exe(value)),
+            Field::Path => {
+                let regex = RegexBuilder::new(&glob_to_regex(&value))
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|error| error.to_string())?;
+                Ok(Term::Path(regex, value))
+            }
+            Field::Title => Ok(Term::Title(value)),
+            Field::Class => Ok(Term::Class(value)),
+            Field::Monitor => match value.parse::<u32>() {
+                Ok(monitor) => Ok(Term::Monitor(monitor)),
+                Err(_) => Err(format!("monitor '{}' is not a number", value)),
+            },
+        }
+    }
+
+    pub fn is_match(&self, window: &WindowInfo) -> bool {
+        match self {
+            Term::Exe(name) => is_same_path(window.get_basename(), name),
+            Term::Path(glob, _) => glob.is_match(&normalize_path(window.get_path())),
+            Term::Title(text) => window
+                .get_title()
+                .to_lowercase()
+                .contains(&text.to_lowercase()),
+            Term::Class(class) => class.eq_ignore_ascii_case(window.get_class()),
+            Term::Monitor(monitor) => window.get_monitor() == *monitor,
+            Term::Regex(field, regex, _) => regex.is_match(&field.get_value(window)),
+        }
+    }
+}
+
+impl Display for Query {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let nested = |query: &Query| match query {
+            Query::And(_) | Query::Or(_) => format!("({})", query),
+            _ => query.to_string(),
+        };
+        match self {
+            Query::And(queries) => {
+                let terms: Vec<String> = queries
+                    .iter()
+                    .map(|query| match query {
+                        Query::Or(_) => nested(query),
+                        _ => query.to_string(),
+                    })
+                    .collect();
+                write!(f, "{}", terms.join(" "))
+            }
+            Query::Or(queries) => {
+                let terms: Vec<String> = queries.iter().map(|query| query.to_string()).collect();
+                write!(f, "{}", terms.join(" | "))
+            }
+            Query::Not(query) => write!(f, "!{}", nested(query)),
+            Query::Term(term) => write!(f, "{}", term),
+        }
+    }
+}
+
+impl Display for Term {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let quote = |value: &str| {
+            if !value.is_empty()
+                && !value
+                    .chars()
+                    .any(|c| c.is_whitespace() || "()|!&\"".contains(c))
+            {
+                value.to_string()
+            } else {
+                format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+        };
+        match self {
+            Term::Exe(value) => write!(f, "exe:{}", quote(value)),
+            Term::Path(_, value) => write!(f, "path:{}", quote(value)),
+            Term::Title(value) => write!(f, "title:{}", quote(value)),
+            Term::Class(value) => write!(f, "class:{}", quote(value)),
+            Term::Monitor(monitor) => write!(f, "monitor:{}", monitor),
+            Term::Regex(field, _, value) => write!(f, "{}~{}", field.get_name(), quote(value)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    Not,
+    Or,
+    And,
+    // field, whether it is a regex, value
+    Term(String, bool, String),
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ParseQueryError> {
+    let chars: Vec<(usize, char)> = source.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let (position, c) = chars[index];
+        let token = match c {
+            c if c.is_whitespace() => {
+                index += 1;
+                continue;
+            }
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '!' => Token::Not,
+            '|' => Token::Or,
+            '&' => Token::And,
+            _ => {
+                let start = index;
+                while index < chars.len() && chars[index].1.is_ascii_alphabetic() {
+                    index += 1;
+                }
+                let word: String = chars[start..index].iter().map(|(_, c)| c).collect();
+                let separator = chars.get(index).map(|(_, c)| *c);
+                match separator {
+                    Some(':') | Some('~') => {}
+                    _ => {
+                        let keyword = match word.to_lowercase().as_str() {
+                            "and" => Some(Token::And),
+                            "or" => Some(Token::Or),
+                            "not" => Some(Token::Not),
+                            _ => None,
+                        };
+                        match keyword {
+                            Some(token) if !word.is_empty() => {
+                                tokens.push((position, token));
+                                continue;
+                            }
+                            _ => {
+                                return Err(ParseQueryError::new(
+                                    source,
+                                    position,
+                                    "expected 'field:value' or 'field~value'",
+                                ))
+                            }
+                        }
+                    }
+                }
+                index += 1;
+                let value = read_value(source, &chars, &mut index)?;
+                tokens.push((position, Token::Term(word, separator == Some('~'), value)));
+                continue;
+            }
+        };
+        tokens.push((position, token));
+        index += 1;
+    }
+    Ok(tokens)
+}
+
+// quoted with `\` escapes, or everything up to whitespace, a bracket or an
+// operator, values containing them are quoted
+fn read_value(
+    source: &str,
+    chars: &[(usize, char)],
+    index: &mut usize,
+) -> Result<String, ParseQueryError> {
+    let mut value = String::new();
+    if chars.get(*index).map(|(_, c)| *c) != Some('"') {
+        while let Some((_, c)) = chars.get(*index) {
+            if c.is_whitespace() || "()|&!".contains(*c) {
+                break;
+            }
+            value.push(*c);
+            *index += 1;
+        }
+        return Ok(value);
+    }
+    let (start, _) = chars[*index];
+    *index += 1;
+    while let Some((_, c)) = chars.get(*index) {
+        *index += 1;
+        match c {
+            '"' => return Ok(value),
+            '\\' => match chars.get(*index) {
+                Some((_, escaped)) => {
+                    value.push(*escaped);
+                    *index += 1;
+                }
+                None => break,
+            },
+            c => value.push(*c),
+        }
+    }
+    Err(ParseQueryError::new(source, start, "unterminated quote"))
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&(usize, Token)> {
+        self.tokens.get(self.index)
+    }
+
+    fn next_is(&self, token: &Token) -> bool {
+        matches!(self.peek(), Some((_, next)) if next == token)
+    }
+
+    fn end(&self) -> usize {
+        self.source.len()
+    }
+
+    fn parse_or(&mut self) -> Result<Query, ParseQueryError> {
+        let mut queries = vec![self.parse_and()?];
+        while self.next_is(&Token::Or) {
+            self.index += 1;
+            queries.push(self.parse_and()?);
+        }
+        Ok(match queries.len() {
+            1 => queries.remove(0),
+            _ => Query::Or(queries),
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Query, ParseQueryError> {
+        let mut queries = vec![self.parse_unary()?];
+        loop {
+            match self.peek() {
+                Some((_, Token::And)) => {
+                    self.index += 1;
+                }
+                Some((_, Token::Open)) | Some((_, Token::Not)) | Some((_, Token::Term(..))) => {}
+                _ => break,
+            }
+            queries.push(self.parse_unary()?);
+        }
+        Ok(match queries.len() {
+            1 => queries.remove(0),
+            _ => Query::And(queries),
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Query, ParseQueryError> {
+        let (position, token) = match self.tokens.get(self.index) {
+            Some((position, token)) => (*position, token),
+            None => {
+                return Err(ParseQueryError::new(
+                    self.source,
+                    self.end(),
+                    "unexpected end",
+                ))
+            }
+        };
+        self.index += 1;
+        match token {
+            Token::Not => Ok(Query::Not(Box::new(self.parse_unary()?))),
+            Token::Open => {
+                let query = self.parse_or()?;
+                if !self.next_is(&Token::Close) {
+                    return Err(ParseQueryError::new(self.source, position, "unclosed '('"));
+                }
+                self.index += 1;
+                Ok(query)
+            }
+            Token::Term(name, regex, value) => {
+                let field = match Field::parse(&name.to_lowercase()) {
+                    Some(field) => field,
+                    None => {
+                        return Err(ParseQueryError::new(
+                            self.source,
+                            position,
+                            &format!("unknown field '{}'", name),
+                        ))
+                    }
+                };
+                Term::new(field, *regex, value.clone())
+                    .map(Query::Term)
+                    .map_err(|message| ParseQueryError::new(self.source, position, &message))
+            }
+            _ => Err(ParseQueryError::new(
+                self.source,
+                position,
+                "expected a term",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use windows::Win32::Foundation::HWND;
+
+    use crate::utils::{FakeBackend, WindowInfo};
+
+    use super::Query;
+
+    fn window(path: &str, title: &str, class: &str, monitor: u32) -> WindowInfo {
+        let mut window = WindowInfo::new(HWND(1), path, title, class);
+        window.monitor = monitor;
+        window
+    }
+
+    #[test]
+    fn it_works() {
+        let query =
+            Query::parse(r#"exe:code.exe title~"ektox" !class:ConsoleWindowClass monitor:2"#)
+                .unwrap();
+        let code = window(
+            "C:/Apps/VS Code/Code.exe",
+            "main.rs - ektox - Visual Studio Code",
+            "Chrome_WidgetWin_1",
+            2,
+        );
+        assert!(query.is_match(&code));
+        let mut other = code.clone();
+        other.monitor = 1;
+        assert!(!query.is_match(&other));
+        let mut console = code;
+        console.class = "consolewindowclass".to_string();
+        assert!(!query.is_match(&console));
+    }
+
+    #[test]
+    fn boolean() {
+        let query =
+            Query::parse("(exe:chrome.exe or exe:firefox.exe) and not title:private").unwrap();
+        assert_eq!(
+            query.to_string(),
+            "(exe:chrome.exe | exe:firefox.exe) !title:private"
+        );
+        assert!(query.is_match(&window("C:/chrome.exe", "New Tab", "", 1)));
+        assert!(query.is_match(&window("C:/Firefox.exe", "New Tab", "", 1)));
+        assert!(!query.is_match(&window("C:/firefox.exe", "Private Browsing", "", 1)));
+        assert!(!query.is_match(&window("C:/edge.exe", "New Tab", "", 1)));
+
+        // operators end unquoted values
+        let query = Query::parse("exe:a.exe|exe:b.exe&!class:c").unwrap();
+        assert_eq!(query.to_string(), "exe:a.exe | exe:b.exe !class:c");
+        assert!(query.is_match(&window("C:/b.exe", "", "d", 1)));
+        assert!(!query.is_match(&window("C:/b.exe", "", "c", 1)));
+
+        let query = Query::parse("exe:a.exe exe:b.exe | class:c").unwrap();
+        assert_eq!(query.to_string(), "exe:a.exe exe:b.exe | class:c");
+        assert!(query.is_match(&window("C:/x.exe", "", "C", 1)));
+    }
+
+    #[test]
+    fn display() {
+        let source = r#"path:"C:/Program Files/**/app-*/Discord.exe" title~"\"quoted\"" !(class:a | monitor:1)"#;
+        let query = Query::parse(source).unwrap();
+        assert_eq!(query.to_string(), source);
+        assert_eq!(
+            Query::parse(&query.to_string()).unwrap().to_string(),
+            source
+        );
+    }
+
+    #[test]
+    fn find() {
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/code.exe");
+        let term = backend.add_window(2, "C:/term.exe");
+        backend.set_window_title(term, "pwsh", "ConsoleWindowClass");
+        backend.add_window(3, "C:/code.exe");
+        let query = Query::parse("exe:code.exe | class:consolewindowclass").unwrap();
+        let windows: Vec<isize> = query.find(&backend).iter().map(|w| w.hwnd.0).collect();
+        assert_eq!(windows, vec![1, 2, 3]);
+        let query = Query::parse("!exe:code.exe").unwrap();
+        assert_eq!(query.find(&backend)[0].get_title(), "pwsh");
+    }
+
+    #[test]
+    fn invalid() {
+        let errors = [
+            ("", 0),
+            ("exe", 0),
+            ("size:2", 0),
+            ("exe:a.exe |", 11),
+            ("(exe:a.exe", 0),
+            ("exe:a.exe)", 9),
+            ("title:\"abc", 6),
+            ("title~\"(\"", 0),
+            ("monitor:one", 0),
+            ("monitor~1", 0),
+        ];
+        for (source, position) in errors {
+            let error = Query::parse(source).unwrap_err();
+            assert_eq!(error.get_position(), position, "{}", error);
+        }
+    }
+}
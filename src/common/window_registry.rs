@@ -0,0 +1,204 @@
+use std::time::{Duration, Instant};
+
+use windows::Win32::Foundation::HWND;
+
+use crate::utils::{FrontendFilter, WindowBackend, WindowEvent, WindowInfo, WindowSnapshot};
+
+// windows which can be switched to, kept up to date from window events so that
+// hotkeys resolve against memory instead of enumerating every window and
+// opening every process, everything is enumerated again now and then in case
+// events were missed, events come from win event hooks only as there is no X11
+// backend to watch property notifies with yet
+pub struct WindowRegistry {
+    // z-order from top to bottom as far as events tell
+    windows: Vec<WindowInfo>,
+    filter: FrontendFilter,
+    sweep_interval: Duration,
+    last_sweep: Option<Instant>,
+}
+
+impl WindowRegistry {
+    pub fn new(sweep_interval: Duration) -> Self {
+        WindowRegistry {
+            windows: Vec::new(),
+            filter: FrontendFilter::default(),
+            sweep_interval,
+            last_sweep: None,
+        }
+    }
+
+    // windows kept by the registry, others have to be enumerated
+    pub fn get_filter(&self) -> &FrontendFilter {
+        &self.filter
+    }
+
+    // apply pending events, or enumerate again when it's time to
+    pub fn update(&mut self, backend: &mut dyn WindowBackend, now: Instant) {
+        let events = backend.take_events();
+        self.apply(backend, &events, now);
+    }
+
+    // true if everything was enumerated again instead
+    pub fn apply(
+        &mut self,
+        backend: &dyn WindowBackend,
+        events: &[WindowEvent],
+        now: Instant,
+    ) -> bool {
+        let due = self
+            .last_sweep
+            .is_none_or(|last| now.duration_since(last) >= self.sweep_interval);
+        if due || events.contains(&WindowEvent::Resync) {
+            self.sweep(backend, now);
+            return true;
+        }
+        for event in events {
+            self.handle(backend, *event);
+        }
+        false
+    }
+
+    pub fn sweep(&mut self, backend: &dyn WindowBackend, now: Instant) {
+        self.windows = backend.snapshot_with(&self.filter).into_vec();
+        self.last_sweep = Some(now);
+    }
+
+    pub fn handle(&mut self, backend: &dyn WindowBackend, event: WindowEvent) {
+        match event {
+            WindowEvent::Destroyed(hwnd) => self.remove(hwnd),
+            WindowEvent::Created(hwnd) | WindowEvent::Foreground(hwnd) => {
+                self.remove(hwnd);
+                if backend.is_eligible(hwnd, &self.filter) {
+                    self.windows.insert(0, backend.describe(hwnd));
+                }
+            }
+            WindowEvent::TitleChanged(hwnd) | WindowEvent::StateChanged(hwnd) => {
+                let eligible = backend.is_eligible(hwnd, &self.filter);
+                match self.windows.iter_mut().find(|window| window.hwnd == hwnd) {
+                    Some(window) if eligible => *window = backend.describe(hwnd),
+                    Some(_) => self.remove(hwnd),
+                    // shown or uncloaked, windows appear on top
+                    None if eligible => self.windows.insert(0, backend.describe(hwnd)),
+                    None => {}
+                }
+            }
+            // handled by update
+            WindowEvent::Resync => {}
+        }
+    }
+
+    pub fn get(&self, hwnd: HWND) -> Option<&WindowInfo> {
+        self.windows.iter().find(|window| window.hwnd == hwnd)
+    }
+
+    fn remove(&mut self, hwnd: HWND) {
+        self.windows.retain(|window| window.hwnd != hwnd);
+    }
+
+    pub fn snapshot(&self) -> WindowSnapshot {
+        self.windows
+            .iter()
+            .enumerate()
+            .map(|(z_order, window)| WindowInfo {
+                z_order,
+                ..window.clone()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use windows::Win32::Foundation::HWND;
+
+    use crate::utils::{FakeBackend, WindowBackend, WindowEvent, WindowState};
+
+    use super::WindowRegistry;
+
+    fn hwnds(registry: &WindowRegistry) -> Vec<HWND> {
+        registry.snapshot().get_hwnds()
+    }
+
+    #[test]
+    fn it_works() {
+        let start = Instant::now();
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/code.exe");
+        backend.add_window(2, "C:/term.exe");
+        let mut registry = WindowRegistry::new(Duration::from_secs(30));
+        registry.update(&mut backend, start);
+        assert_eq!(hwnds(&registry), vec![HWND(1), HWND(2)]);
+
+        backend.add_window(3, "C:/chrome.exe");
+        backend.focus(HWND(2));
+        backend.set_window_title(HWND(1), "main.rs - ektox", "");
+        backend.close(HWND(3));
+        registry.update(&mut backend, start + Duration::from_secs(1));
+        assert_eq!(hwnds(&registry), vec![HWND(2), HWND(1)]);
+        assert_eq!(registry.snapshot()[1].get_title(), "main.rs - ektox");
+        assert_eq!(registry.snapshot()[1].get_z_order(), 1);
+    }
+
+    #[test]
+    fn synthetic_events() {
+        let start = Instant::now();
+        let mut backend = FakeBackend::new();
+        let mut registry = WindowRegistry::new(Duration::from_secs(30));
+        registry.update(&mut backend, start);
+        assert!(hwnds(&registry).is_empty());
+
+        // windows are described when events arrive, not when they are looked up
+        backend.add_window(1, "C:/code.exe");
+        backend.add_window(2, "C:/term.exe");
+        backend.events = vec![
+            WindowEvent::Created(HWND(1)),
+            WindowEvent::Created(HWND(2)),
+            WindowEvent::Foreground(HWND(1)),
+        ];
+        registry.update(&mut backend, start);
+        assert_eq!(hwnds(&registry), vec![HWND(1), HWND(2)]);
+
+        // hidden windows are dropped until shown again
+        backend.windows[1].state = WindowState::Hidden;
+        backend.events = vec![WindowEvent::StateChanged(HWND(2))];
+        registry.update(&mut backend, start);
+        assert_eq!(hwnds(&registry), vec![HWND(1)]);
+        backend.windows[1].state = WindowState::Normal;
+        backend.events = vec![WindowEvent::StateChanged(HWND(2))];
+        registry.update(&mut backend, start);
+        assert_eq!(hwnds(&registry), vec![HWND(2), HWND(1)]);
+
+        // events for windows which are gone are harmless
+        backend.events = vec![
+            WindowEvent::TitleChanged(HWND(9)),
+            WindowEvent::Destroyed(HWND(9)),
+        ];
+        registry.update(&mut backend, start);
+        assert_eq!(hwnds(&registry).len(), 2);
+    }
+
+    #[test]
+    fn sweep() {
+        let start = Instant::now();
+        let mut backend = FakeBackend::new();
+        backend.add_window(1, "C:/code.exe");
+        let mut registry = WindowRegistry::new(Duration::from_secs(30));
+        registry.update(&mut backend, start);
+
+        // missed events are caught up by the next sweep or a resync
+        backend.add_window(2, "C:/term.exe");
+        backend.events.clear();
+        registry.update(&mut backend, start + Duration::from_secs(10));
+        assert_eq!(hwnds(&registry), vec![HWND(1)]);
+        registry.update(&mut backend, start + Duration::from_secs(40));
+        assert_eq!(hwnds(&registry), vec![HWND(1), HWND(2)]);
+
+        backend.close(HWND(1));
+        backend.events = vec![WindowEvent::Resync];
+        registry.update(&mut backend, start + Duration::from_secs(41));
+        assert_eq!(hwnds(&registry), vec![HWND(2)]);
+        assert_eq!(backend.get_windows(), vec![HWND(2)]);
+    }
+}
@@ -0,0 +1,216 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::utils::{fuzzy_match, FuzzyMatch, WindowInfo, WindowSnapshot};
+
+use super::{Config, Target, WindowHistory};
+
+// most recently used window gets this much on top of its match score,
+// every following one a little less
+const BONUS_RECENT: i64 = 32;
+const BONUS_RECENT_STEP: i64 = 4;
+
+// a window found by the switcher with the characters its fields matched,
+// for highlighting them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwitcherHit {
+    pub window: WindowInfo,
+    pub app: String,
+    pub score: i64,
+    // char indices of title, app and basename matched by the query
+    pub title_positions: Vec<usize>,
+    pub app_positions: Vec<usize>,
+    pub basename_positions: Vec<usize>,
+}
+
+impl SwitcherHit {
+    pub fn get_window(&self) -> &WindowInfo {
+        &self.window
+    }
+
+    pub fn get_app(&self) -> &String {
+        &self.app
+    }
+
+    pub fn get_score(&self) -> i64 {
+        self.score
+    }
+}
+
+// fuzzy search over windows by title, app name and executable name, every
+// word of the query has to match one of them, recently used windows rank higher
+pub struct WindowSwitcher {
+    // window with its app name
+    windows: Vec<(WindowInfo, String)>,
+    // rank in activation history by handle, 0 for the most recent window
+    ranks: HashMap<isize, usize>,
+}
+
+impl WindowSwitcher {
+    // app of a window is its executable name without extension
+    pub fn new(snapshot: WindowSnapshot) -> Self {
+        let windows = snapshot
+            .into_iter()
+            .map(|window| {
+                let basename = window.get_basename();
+                let app = Path::new(basename)
+                    .file_stem()
+                    .map_or(basename.to_string(), |stem| {
+                        stem.to_string_lossy().to_string()
+                    });
+                (window, app)
+            })
+            .collect();
+        WindowSwitcher {
+            windows,
+            ranks: HashMap::new(),
+        }
+    }
+
+    // windows of apps defined in `apps` are found by their name instead
+    pub fn with_apps(mut self, config: &Config) -> Self {
+        let matchers: Vec<_> = config
+            .get_apps()
+            .keys()
+            .filter_map(|name| {
+                let matcher = config.get_matcher(&Target::app(name)).ok()?;
+                Some((name, matcher))
+            })
+            .collect();
+        for (window, app) in self.windows.iter_mut() {
+            if let Some((name, _)) = matchers
+                .iter()
+                .find(|(_, matcher)| matcher.is_match(window))
+            {
+                *app = name.to_string();
+            }
+        }
+        self
+    }
+
+    pub fn with_history(mut self, history: &WindowHistory) -> Self {
+        self.ranks = history
+            .get_recent()
+            .into_iter()
+            .enumerate()
+            .map(|(rank, hwnd)| (hwnd.0, rank))
+            .collect();
+        self
+    }
+
+    // windows matching query, best first, ties keep z-order
+    pub fn search(&self, query: &str) -> Vec<SwitcherHit> {
+        let terms: Vec<&str> = query.split_whitespace().collect();
+        let mut hits: Vec<SwitcherHit> = self
+            .windows
+            .iter()
+            .filter_map(|(window, app)| self.score(window, app, &terms))
+            .collect();
+        hits.sort_by_key(|hit| std::cmp::Reverse(hit.score));
+        hits
+    }
+
+    fn score(&self, window: &WindowInfo, app: &str, terms: &[&str]) -> Option<SwitcherHit> {
+        let recent = self.ranks.get(&window.get_hwnd().0).map_or(0, |rank| {
+            (BONUS_RECENT - BONUS_RECENT_STEP * *rank as i64).max(0)
+        });
+        let basename = window.get_basename();
+        let mut hit = SwitcherHit {
+            window: window.clone(),
+            app: app.to_string(),
+            score: recent,
+            title_positions: Vec::new(),
+            app_positions: Vec::new(),
+            basename_positions: Vec::new(),
+        };
+        for term in terms {
+            let fields = [
+                fuzzy_match(term, window.get_title()),
+                fuzzy_match(term, app),
+                fuzzy_match(term, basename),
+            ];
+            let (field, found) = fields
+                .into_iter()
+                .enumerate()
+                .filter_map(|(field, found)| found.map(|found| (field, found)))
+                // the first field of the best score
+                .fold(
+                    None,
+                    |best: Option<(usize, FuzzyMatch)>, (field, found)| match best {
+                        Some((_, ref best_found)) if best_found.score >= found.score => best,
+                        _ => Some((field, found)),
+                    },
+                )?;
+            hit.score += found.get_score();
+            let positions = match field {
+                0 => &mut hit.title_positions,
+                1 => &mut hit.app_positions,
+                _ => &mut hit.basename_positions,
+            };
+            positions.extend(found.get_positions());
+            positions.sort_unstable();
+            positions.dedup();
+        }
+        Some(hit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use windows::Win32::Foundation::HWND;
+
+    use crate::{
+        common::{Config, WindowHistory},
+        utils::{FakeBackend, WindowBackend},
+    };
+
+    use super::WindowSwitcher;
+
+    #[test]
+    fn it_works() {
+        let mut backend = FakeBackend::new();
+        let code = backend.add_window(1, "C:/Apps/Code.exe");
+        backend.set_window_title(code, "main.rs - ektox - Visual Studio Code", "");
+        let slack = backend.add_window(2, "C:/Apps/slack.exe");
+        backend.set_window_title(slack, "general - Slack", "");
+        let term = backend.add_window(3, "C:/Apps/WindowsTerminal.exe");
+        backend.set_window_title(term, "ektox", "");
+        let config: Config = serde_json::from_value(json!({
+            "startup": false,
+            "apps": { "chat": { "exec": ["C:/Apps/slack.exe"] } },
+            "actions": []
+        }))
+        .unwrap();
+        let switcher = WindowSwitcher::new(backend.snapshot()).with_apps(&config);
+        let hwnds = |query: &str| -> Vec<HWND> {
+            switcher
+                .search(query)
+                .iter()
+                .map(|hit| hit.get_window().get_hwnd())
+                .collect()
+        };
+
+        // everything in z-order without a query
+        assert_eq!(hwnds(""), vec![code, slack, term]);
+        assert_eq!(hwnds("vsc"), vec![code]);
+        assert_eq!(hwnds("chat"), vec![slack]);
+        assert_eq!(hwnds("wt ektox"), vec![term]);
+        assert!(hwnds("zzz").is_empty());
+
+        let hits = switcher.search("ektox main");
+        assert_eq!(hits[0].get_window().get_hwnd(), code);
+        assert_eq!(
+            hits[0].title_positions,
+            vec![0, 1, 2, 3, 10, 11, 12, 13, 14]
+        );
+        let hits = switcher.search("chat");
+        assert_eq!(hits[0].get_app(), "chat");
+        assert_eq!(hits[0].app_positions, vec![0, 1, 2, 3]);
+
+        // recently used windows rank higher
+        let mut history = WindowHistory::new(8);
+        history.activate(term, "C:/Apps/WindowsTerminal.exe");
+        let switcher = WindowSwitcher::new(backend.snapshot()).with_history(&history);
+        assert_eq!(switcher.search("")[0].get_window().get_hwnd(), term);
+    }
+}
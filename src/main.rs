@@ -1,9 +1,71 @@
 #![windows_subsystem = "windows"]
-use ektox::{common::App, utils::MessageBox};
+use ektox::{
+    common::App,
+    utils::{Console, MessageBox},
+};
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("migrate") {
+        let dry_run = args.iter().any(|arg| arg == "--dry-run");
+        match App::migrate(dry_run) {
+            Ok(summary) => MessageBox::info(summary.as_str()),
+            Err(error) => MessageBox::error(error.to_string().as_str()),
+        }
+        return;
+    }
+    if args.first().map(String::as_str) == Some("check") {
+        match App::check() {
+            Ok(summary) => MessageBox::info(summary.as_str()),
+            Err(error) => MessageBox::error(error.to_string().as_str()),
+        }
+        return;
+    }
+    if args.first().map(String::as_str) == Some("history") {
+        match App::history() {
+            Ok(windows) => Console::print(windows.as_str()),
+            Err(error) => Console::print(error.to_string().as_str()),
+        }
+        return;
+    }
+    if args.first().map(String::as_str) == Some("pick") && args.iter().any(|arg| arg == "--tui") {
+        if !Console::open() {
+            MessageBox::error("Open Console Failed");
+            return;
+        }
+        if let Err(error) = App::pick_tui() {
+            Console::print(error.to_string().as_str());
+        }
+        return;
+    }
+    if args.first().map(String::as_str) == Some("pick") {
+        // ektox has no console input to type a query into
+        let query = args[1..].join(" ");
+        if query.is_empty() {
+            Console::print("Usage: ektox pick QUERY, or ektox pick --tui");
+            return;
+        }
+        match App::pick(&query) {
+            Ok(window) => Console::print(window.as_str()),
+            Err(error) => Console::print(error.to_string().as_str()),
+        }
+        return;
+    }
+    if args.first().map(String::as_str) == Some("list-windows") {
+        let json = args.iter().any(|arg| arg == "--json");
+        let query: Vec<&str> = args[1..]
+            .iter()
+            .map(String::as_str)
+            .filter(|arg| *arg != "--json")
+            .collect();
+        match App::list_windows(&query.join(" "), json) {
+            Ok(windows) => Console::print(windows.as_str()),
+            Err(error) => Console::print(error.to_string().as_str()),
+        }
+        return;
+    }
     match App::init() {
-        Ok(app) => {
+        Ok(mut app) => {
             app.start();
         }
         Err(error) => {
@@ -0,0 +1,49 @@
+use windows::Win32::{
+    Foundation::HWND,
+    System::Console::{
+        AllocConsole, AttachConsole, FreeConsole, GetConsoleWindow, ATTACH_PARENT_PROCESS,
+    },
+};
+
+use super::MessageBox;
+
+pub struct Console;
+
+impl Console {
+    // ektox has no console of its own, borrow the one of the shell which started it
+    pub fn attach() -> bool {
+        if !cfg!(windows) {
+            return true;
+        }
+        unsafe { AttachConsole(ATTACH_PARENT_PROCESS).as_bool() }
+    }
+
+    // a console window of its own for reading keys, the console of the shell
+    // would be read by the shell at the same time
+    pub fn open() -> bool {
+        if !cfg!(windows) {
+            return true;
+        }
+        unsafe {
+            FreeConsole();
+            AllocConsole().as_bool()
+        }
+    }
+
+    // window of the console opened by the process
+    pub fn get_window() -> HWND {
+        if !cfg!(windows) {
+            return HWND(0);
+        }
+        unsafe { GetConsoleWindow() }
+    }
+
+    // print to the console, show a message box when started without one
+    pub fn print(text: &str) {
+        if Self::attach() {
+            println!("{}", text);
+        } else {
+            MessageBox::info(text);
+        }
+    }
+}
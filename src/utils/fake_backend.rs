@@ -0,0 +1,266 @@
+use std::{cell::RefCell, io, process::Command, rc::Rc};
+
+use windows::Win32::{
+    Foundation::HWND,
+    UI::WindowsAndMessaging::{WS_EX_TOOLWINDOW, WS_POPUP, WS_VISIBLE},
+};
+
+use super::{
+    FrontendFilter, Hotkey, Rect, WindowBackend, WindowCandidate, WindowEvent, WindowFilter,
+    WindowState,
+};
+
+pub struct FakeWindow {
+    pub hwnd: HWND,
+    pub pid: u32,
+    pub process_name: String,
+    pub title: String,
+    pub class: String,
+    pub monitor: u32,
+    pub rect: Rect,
+    pub owner: HWND,
+    pub popup: bool,
+    pub tool_window: bool,
+    pub state: WindowState,
+    // state the window returns to when restored from minimized
+    pub restore_state: WindowState,
+}
+
+// in-memory window system for tests, windows are kept in z-order from top to bottom
+#[derive(Default)]
+pub struct FakeBackend {
+    pub windows: Vec<FakeWindow>,
+    pub foreground: HWND,
+    // programs with arguments of spawned processes, shared so tests can
+    // inspect it after the backend is handed to an executor
+    pub spawned: Rc<RefCell<Vec<String>>>,
+    pub sent_keys: Vec<String>,
+    // spawned processes open a window of the program at the bottom of z-order
+    pub spawn_window: bool,
+    pub closed: Vec<HWND>,
+    // what a window system would report for the changes made through the backend
+    pub events: Vec<WindowEvent>,
+}
+
+impl FakeBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // new window is put at the bottom of z-order, its pid is the id
+    // and its title the file name of the process
+    pub fn add_window(&mut self, id: isize, process_name: &str) -> HWND {
+        let hwnd = HWND(id);
+        self.events.push(WindowEvent::Created(hwnd));
+        self.windows.push(FakeWindow {
+            hwnd,
+            pid: id as u32,
+            process_name: process_name.to_string(),
+            title: process_name
+                .rsplit(['/', '\\'])
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            class: String::new(),
+            monitor: 1,
+            rect: Rect::new(0, 0, 800, 600),
+            owner: HWND(0),
+            popup: false,
+            tool_window: false,
+            state: WindowState::Normal,
+            restore_state: WindowState::Normal,
+        });
+        hwnd
+    }
+
+    pub fn set_window_title(&mut self, hwnd: HWND, title: &str, class: &str) {
+        if let Some(window) = self.get_window_mut(hwnd) {
+            window.title = title.to_string();
+            window.class = class.to_string();
+        }
+        self.events.push(WindowEvent::TitleChanged(hwnd));
+    }
+
+    pub fn set_window_state(&mut self, hwnd: HWND, state: WindowState) {
+        if let Some(window) = self.get_window_mut(hwnd) {
+            window.state = state;
+        }
+        self.events.push(WindowEvent::StateChanged(hwnd));
+    }
+
+    fn get_candidate(window: &FakeWindow) -> WindowCandidate {
+        let mut style = 0;
+        if window.state != WindowState::Hidden {
+            style |= WS_VISIBLE.0;
+        }
+        if window.popup {
+            style |= WS_POPUP.0;
+        }
+        WindowCandidate {
+            hwnd: window.hwnd,
+            owner: window.owner,
+            title_len: window.title.len(),
+            style,
+            ex_style: if window.tool_window {
+                WS_EX_TOOLWINDOW.0
+            } else {
+                0
+            },
+            rect: window.rect,
+            cloaked: window.state == WindowState::Cloaked,
+        }
+    }
+
+    pub fn get_window(&self, hwnd: HWND) -> Option<&FakeWindow> {
+        self.windows.iter().find(|window| window.hwnd == hwnd)
+    }
+
+    fn get_window_mut(&mut self, hwnd: HWND) -> Option<&mut FakeWindow> {
+        self.windows.iter_mut().find(|window| window.hwnd == hwnd)
+    }
+}
+
+impl WindowBackend for FakeBackend {
+    fn get_windows_with(&self, filter: &FrontendFilter) -> Vec<HWND> {
+        let mut filter = *filter;
+        self.windows
+            .iter()
+            .filter(|window| filter.is_match(&Self::get_candidate(window)))
+            .map(|window| window.hwnd)
+            .collect()
+    }
+
+    fn is_eligible(&self, hwnd: HWND, filter: &FrontendFilter) -> bool {
+        let mut filter = *filter;
+        self.get_window(hwnd)
+            .is_some_and(|window| filter.is_match(&Self::get_candidate(window)))
+    }
+
+    fn get_pid(&self, hwnd: HWND) -> u32 {
+        self.get_window(hwnd)
+            .map(|window| window.pid)
+            .unwrap_or_default()
+    }
+
+    fn get_process_name(&self, hwnd: HWND) -> String {
+        self.get_window(hwnd)
+            .map(|window| window.process_name.clone())
+            .unwrap_or_default()
+    }
+
+    fn get_title(&self, hwnd: HWND) -> String {
+        self.get_window(hwnd)
+            .map(|window| window.title.clone())
+            .unwrap_or_default()
+    }
+
+    fn get_class(&self, hwnd: HWND) -> String {
+        self.get_window(hwnd)
+            .map(|window| window.class.clone())
+            .unwrap_or_default()
+    }
+
+    fn get_monitor(&self, hwnd: HWND) -> u32 {
+        self.get_window(hwnd)
+            .map(|window| window.monitor)
+            .unwrap_or_default()
+    }
+
+    fn get_rect(&self, hwnd: HWND) -> Rect {
+        self.get_window(hwnd)
+            .map(|window| window.rect)
+            .unwrap_or_default()
+    }
+
+    fn get_owner(&self, hwnd: HWND) -> HWND {
+        self.get_window(hwnd)
+            .map(|window| window.owner)
+            .unwrap_or_default()
+    }
+
+    fn get_foreground_window(&self) -> HWND {
+        self.foreground
+    }
+
+    fn get_state(&self, hwnd: HWND) -> WindowState {
+        self.get_window(hwnd)
+            .map(|window| window.state)
+            .unwrap_or_default()
+    }
+
+    fn restore(&mut self, hwnd: HWND) {
+        if let Some(window) = self.get_window_mut(hwnd) {
+            if window.state == WindowState::Minimized {
+                window.state = window.restore_state;
+                self.events.push(WindowEvent::StateChanged(hwnd));
+            }
+        }
+    }
+
+    fn focus(&mut self, hwnd: HWND) {
+        self.restore(hwnd);
+        if let Some(index) = self.windows.iter().position(|window| window.hwnd == hwnd) {
+            let window = self.windows.remove(index);
+            self.windows.insert(0, window);
+            self.foreground = hwnd;
+            self.events.push(WindowEvent::Foreground(hwnd));
+        }
+    }
+
+    fn close(&mut self, hwnd: HWND) {
+        self.windows.retain(|window| window.hwnd != hwnd);
+        self.closed.push(hwnd);
+        self.events.push(WindowEvent::Destroyed(hwnd));
+        if self.foreground == hwnd {
+            self.foreground = self.windows.first().map(|w| w.hwnd).unwrap_or_default();
+        }
+    }
+
+    fn minimize(&mut self, hwnd: HWND) {
+        if let Some(window) = self.get_window_mut(hwnd) {
+            if window.state != WindowState::Minimized {
+                window.restore_state = window.state;
+                window.state = WindowState::Minimized;
+            }
+        }
+        self.events.push(WindowEvent::StateChanged(hwnd));
+        if self.foreground == hwnd {
+            self.foreground = HWND(0);
+        }
+    }
+
+    fn maximize(&mut self, hwnd: HWND) {
+        if let Some(window) = self.get_window_mut(hwnd) {
+            window.state = WindowState::Maximized;
+        }
+        self.events.push(WindowEvent::StateChanged(hwnd));
+    }
+
+    fn spawn(&mut self, command: Command) -> io::Result<u32> {
+        let mut line = command.get_program().to_string_lossy().to_string();
+        for arg in command.get_args() {
+            line.push(' ');
+            line.push_str(&arg.to_string_lossy());
+        }
+        let pid = {
+            let mut spawned = self.spawned.borrow_mut();
+            spawned.push(line);
+            1000 + spawned.len() as u32
+        };
+        if self.spawn_window {
+            let program = command.get_program().to_string_lossy().to_string();
+            self.add_window(pid as isize, &program);
+        }
+        Ok(pid)
+    }
+
+    fn send_keys(&mut self, keys: &[Hotkey]) {
+        for key in keys.iter() {
+            self.sent_keys.push(key.to_string());
+        }
+    }
+
+    fn take_events(&mut self) -> Vec<WindowEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
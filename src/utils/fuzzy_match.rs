@@ -0,0 +1,148 @@
+// pattern found in a text with its characters in order, not necessarily next
+// to each other, the best scoring placement is kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    // char indices of text matched by the pattern
+    pub positions: Vec<usize>,
+}
+
+impl FuzzyMatch {
+    pub fn get_score(&self) -> i64 {
+        self.score
+    }
+
+    pub fn get_positions(&self) -> &Vec<usize> {
+        &self.positions
+    }
+}
+
+const SCORE_MATCH: i64 = 16;
+// beginning of a word, so that acronyms like `vsc` find `Visual Studio Code`
+const BONUS_BOUNDARY: i64 = 24;
+// upper case letter after a lower case one, like `S` of `WindowsTerminal`
+const BONUS_CAMEL: i64 = 20;
+const BONUS_CONSECUTIVE: i64 = 16;
+// per character skipped between two matched characters
+const PENALTY_GAP: i64 = 1;
+// per character before the first matched one, at most PENALTY_LEADING_MAX
+const PENALTY_LEADING: i64 = 1;
+const PENALTY_LEADING_MAX: i64 = 8;
+
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || "-_./\\:()[]|,".contains(c)
+}
+
+fn bonus(previous: Option<char>, current: char) -> i64 {
+    match previous {
+        None => BONUS_BOUNDARY,
+        Some(previous) if is_separator(previous) && !is_separator(current) => BONUS_BOUNDARY,
+        Some(previous) if previous.is_lowercase() && current.is_uppercase() => BONUS_CAMEL,
+        Some(previous) if !previous.is_ascii_digit() && current.is_ascii_digit() => BONUS_CAMEL,
+        _ => 0,
+    }
+}
+
+fn is_same_char(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+// match pattern against text ignoring case, None if text does not contain
+// every character of pattern in order, an empty pattern matches anything
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    if pattern.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+    if pattern.len() > text.len() {
+        return None;
+    }
+    let bonuses: Vec<i64> = (0..text.len())
+        .map(|j| bonus(j.checked_sub(1).map(|k| text[k]), text[j]))
+        .collect();
+
+    // scores[i][j] is the best score with pattern[i] matched at text[j],
+    // parents[i][j] where pattern[i - 1] was matched then
+    let mut scores: Vec<Vec<Option<i64>>> = vec![vec![None; text.len()]; pattern.len()];
+    let mut parents: Vec<Vec<usize>> = vec![vec![0; text.len()]; pattern.len()];
+    for (i, p) in pattern.iter().enumerate() {
+        // best placement of pattern[i - 1] before j - 1 with the gap to j paid
+        let mut gapped: Option<(i64, usize)> = None;
+        for j in i..text.len() {
+            if i > 0 && j >= 2 {
+                let candidate = scores[i - 1][j - 2].map(|score| (score - PENALTY_GAP, j - 2));
+                gapped = match (gapped, candidate) {
+                    (Some((best, k)), Some((score, _))) if best - PENALTY_GAP >= score => {
+                        Some((best - PENALTY_GAP, k))
+                    }
+                    (_, Some(candidate)) => Some(candidate),
+                    (Some((best, k)), None) => Some((best - PENALTY_GAP, k)),
+                    (None, None) => None,
+                };
+            }
+            if !is_same_char(*p, text[j]) {
+                continue;
+            }
+            let base = SCORE_MATCH + bonuses[j];
+            if i == 0 {
+                let leading = (j as i64 * PENALTY_LEADING).min(PENALTY_LEADING_MAX);
+                scores[i][j] = Some(base - leading);
+                continue;
+            }
+            let consecutive = scores[i - 1][j - 1].map(|score| (score + BONUS_CONSECUTIVE, j - 1));
+            let best = match (consecutive, gapped) {
+                (Some(a), Some(b)) => Some(if a.0 >= b.0 { a } else { b }),
+                (a, b) => a.or(b),
+            };
+            if let Some((score, parent)) = best {
+                scores[i][j] = Some(base + score);
+                parents[i][j] = parent;
+            }
+        }
+    }
+
+    let last = pattern.len() - 1;
+    let (mut j, score) = scores[last]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, score)| score.map(|score| (j, score)))
+        // earliest placement of the best score
+        .fold(None, |best: Option<(usize, i64)>, (j, score)| match best {
+            Some((_, best_score)) if best_score >= score => best,
+            _ => Some((j, score)),
+        })?;
+    let mut positions = vec![0; pattern.len()];
+    for i in (0..pattern.len()).rev() {
+        positions[i] = j;
+        j = parents[i][j];
+    }
+    Some(FuzzyMatch { score, positions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fuzzy_match;
+
+    #[test]
+    fn it_works() {
+        assert_eq!(fuzzy_match("", "anything").unwrap().get_score(), 0);
+        assert!(fuzzy_match("xyz", "Visual Studio Code").is_none());
+        assert!(fuzzy_match("codes", "code").is_none());
+
+        // acronyms prefer word boundaries over the first occurrence
+        let found = fuzzy_match("vsc", "Visual Studio Code").unwrap();
+        assert_eq!(found.get_positions(), &vec![0, 7, 14]);
+        let found = fuzzy_match("wt", "WindowsTerminal.exe").unwrap();
+        assert_eq!(found.get_positions(), &vec![0, 7]);
+
+        // consecutive characters and word starts rank higher
+        let score = |pattern, text| fuzzy_match(pattern, text).unwrap().get_score();
+        assert!(score("term", "Terminal") > score("term", "The Rest Mode"));
+        assert!(score("code", "code.exe") > score("code", "barcode.exe"));
+        assert!(score("sl", "Slack") > score("sl", "Visual"));
+    }
+}
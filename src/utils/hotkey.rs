@@ -5,14 +5,15 @@ use self::parse_hotkey_error::{ParseHotkeyError, ParseHotkeyErrorKind};
 use self::special_key::SpecialKey;
 use serde::de::Visitor;
 use windows::Win32::UI::Input::KeyboardAndMouse::{
-    HOT_KEY_MODIFIERS, MOD_ALT, MOD_CONTROL, MOD_SHIFT, MOD_WIN,
+    HOT_KEY_MODIFIERS, MOD_ALT, MOD_CONTROL, MOD_SHIFT, MOD_WIN, VK_CONTROL, VK_LWIN, VK_MENU,
+    VK_SHIFT,
 };
 
 pub mod key;
 pub mod parse_hotkey_error;
 pub mod special_key;
 
-#[derive(Eq, Debug)]
+#[derive(Eq, Debug, Default, Clone)]
 pub struct Hotkey {
     ctrl: bool,
     shift: bool,
@@ -39,21 +40,12 @@ impl Hotkey {
             key,
         }
     }
-    pub fn default() -> Self {
-        Hotkey {
-            ctrl: false,
-            alt: false,
-            shift: false,
-            win: false,
-            key: None,
-        }
-    }
-
     pub fn parse(source: &str) -> Result<Self, ParseHotkeyError> {
         let mut hotkey = Hotkey::default();
         let mut error = ParseHotkeyError::default();
         let mut parse_error = false;
         let part_of_keys: Vec<&str> = source.split('+').map(|part| part.trim()).collect();
+        #[allow(clippy::never_loop)]
         loop {
             // if part_of_keys is empty or just one
             // like:
@@ -73,7 +65,7 @@ impl Hotkey {
                 // 1: "ctrl + 1 + 2"
                 // 2: "ctrl + Delete + BackSpace"
                 // 3: "ctrl + 1 + Delete"
-                if hotkey.key != None {
+                if hotkey.key.is_some() {
                     parse_error = true;
                     error = ParseHotkeyError::new(source, ParseHotkeyErrorKind::TooManyKey);
                     break;
@@ -89,7 +81,7 @@ impl Hotkey {
                 break;
             }
             //  finaryll hotkey must contains 1 key and minimum 1 modifier
-            if !parse_error && hotkey.key == None {
+            if !parse_error && hotkey.key.is_none() {
                 parse_error = true;
                 error = ParseHotkeyError::new(source, ParseHotkeyErrorKind::MissingKey);
             }
@@ -164,6 +156,30 @@ impl Hotkey {
             None => 0,
         }
     }
+
+    // virtual keys to press in order, modifiers first
+    pub fn get_virtual_keys(&self) -> Vec<u16> {
+        let mut keys = Vec::new();
+        if self.ctrl {
+            keys.push(VK_CONTROL.0);
+        }
+        if self.shift {
+            keys.push(VK_SHIFT.0);
+        }
+        if self.alt {
+            keys.push(VK_MENU.0);
+        }
+        if self.win {
+            keys.push(VK_LWIN.0);
+        }
+        match &self.key {
+            // virtual key of letters is the upper case ascii code
+            Some(Key::AlphaNumeric(c)) => keys.push(c.to_ascii_uppercase() as u16),
+            Some(key) => keys.push(key.as_u32() as u16),
+            None => {}
+        }
+        keys
+    }
 }
 
 impl Display for Hotkey {
@@ -229,7 +245,9 @@ impl<'de> serde::Deserialize<'de> for Hotkey {
 #[cfg(test)]
 mod tests {
 
-    use windows::Win32::UI::Input::KeyboardAndMouse::{MOD_ALT, MOD_CONTROL, VK_DELETE};
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        MOD_ALT, MOD_CONTROL, VK_CONTROL, VK_DELETE, VK_MENU, VK_SHIFT,
+    };
 
     use crate::utils::hotkey::{
         parse_hotkey_error::{ParseHotkeyError, ParseHotkeyErrorKind},
@@ -314,6 +332,18 @@ mod tests {
         assert_eq!(hotkey.get_key(), VK_DELETE.0 as u32);
     }
 
+    #[test]
+    fn get_virtual_keys() {
+        let hotkey = Hotkey::parse("ctrl + alt + c").unwrap();
+        assert_eq!(
+            hotkey.get_virtual_keys(),
+            vec![VK_CONTROL.0, VK_MENU.0, 'C' as u16]
+        );
+
+        let hotkey = Hotkey::parse("shift + delete").unwrap();
+        assert_eq!(hotkey.get_virtual_keys(), vec![VK_SHIFT.0, VK_DELETE.0]);
+    }
+
     #[test]
     fn to_string() {
         let hotkey = Hotkey::parse("ctrl + alt + delete").unwrap();
@@ -1,7 +1,7 @@
 use std::fmt::Display;
 
 use super::SpecialKey;
-#[derive(Eq, Debug)]
+#[derive(Eq, Debug, Clone)]
 pub enum Key {
     AlphaNumeric(char),
     Special(SpecialKey),
@@ -38,7 +38,7 @@ impl Key {
 impl Display for Key {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let str = match &self {
-            Key::AlphaNumeric(c) => String::from(c.to_string()),
+            Key::AlphaNumeric(c) => c.to_string(),
             Key::Special(s) => s.to_string(),
         };
         write!(f, "{}", str)
@@ -1,6 +1,28 @@
+pub use self::console::Console;
+#[cfg(test)]
+pub use self::fake_backend::{FakeBackend, FakeWindow};
+pub use self::fuzzy_match::{fuzzy_match, FuzzyMatch};
 pub use self::hotkey::Hotkey;
 pub use self::message_box::MessageBox;
+pub use self::path_expander::{ExpandPathError, PathExpander};
+pub use self::window_backend::{Win32Backend, WindowBackend};
+pub use self::window_event::{WindowEvent, WindowEventHook};
+pub use self::window_filter::*;
 pub use self::window_finder::*;
+pub use self::window_info::{Rect, WindowInfo};
+pub use self::window_snapshot::WindowSnapshot;
+pub use self::window_state::{WindowPlacement, WindowState};
+mod console;
+#[cfg(test)]
+mod fake_backend;
+mod fuzzy_match;
 mod hotkey;
 mod message_box;
+mod path_expander;
+mod window_backend;
+mod window_event;
+mod window_filter;
 mod window_finder;
+mod window_info;
+mod window_snapshot;
+mod window_state;
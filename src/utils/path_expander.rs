@@ -0,0 +1,220 @@
+use std::fmt::Display;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExpandPathError {
+    source: String,
+    variable: String,
+}
+
+impl ExpandPathError {
+    pub fn new(source: &str, variable: &str) -> Self {
+        ExpandPathError {
+            source: source.to_string(),
+            variable: variable.to_string(),
+        }
+    }
+}
+
+impl Display for ExpandPathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ExpandPathError: unknown variable '{}' in '{}'",
+            self.variable, self.source
+        )
+    }
+}
+
+pub type VariableLookup = Box<dyn Fn(&str) -> Option<String>>;
+
+// expand variables in a path
+// 1: "%LOCALAPPDATA%/app.exe"  windows style environment variable
+// 2: "${HOME}/app"             shell style environment variable
+// 3: "~/app"                   home directory
+// 4: "{ProgramFiles}/app.exe"  known folder, other names in braces are kept
+//                              as written, like "app-{v2}" or "{0}"
+pub struct PathExpander {
+    lookup: VariableLookup,
+}
+
+impl PathExpander {
+    pub fn new(lookup: VariableLookup) -> Self {
+        PathExpander { lookup }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(Box::new(|name| std::env::var(name).ok()))
+    }
+
+    // expand variables, everything else is kept as written, like the quotes
+    // and backslashes of a command line
+    pub fn expand(&self, source: &str) -> Result<String, ExpandPathError> {
+        let mut result = String::new();
+        let mut rest = source;
+
+        if rest == "~" || rest.starts_with("~/") || rest.starts_with("~\\") {
+            result.push_str(&self.known_folder(source, "Home")?);
+            rest = &rest[1..];
+        }
+
+        while let Some(position) = rest.find(['%', '$', '{']) {
+            result.push_str(&rest[..position]);
+            let tail = &rest[position..];
+            if let Some(name) = tail.strip_prefix("%%") {
+                result.push('%');
+                rest = name;
+            } else if let Some((name, next)) = enclosed(tail, "%", "%") {
+                result.push_str(&self.variable(source, name)?);
+                rest = next;
+            } else if let Some((name, next)) = enclosed(tail, "${", "}") {
+                result.push_str(&self.variable(source, name)?);
+                rest = next;
+            } else if let Some((name, next)) =
+                enclosed(tail, "{", "}").filter(|(name, _)| known_folder_variables(name).is_some())
+            {
+                result.push_str(&self.known_folder(source, name)?);
+                rest = next;
+            } else {
+                result.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+
+    // expand a path matched against process names, backslashes are replaced
+    // by slashes like process names of windows
+    pub fn expand_path(&self, source: &str) -> Result<String, ExpandPathError> {
+        Ok(self.expand(source)?.replace('\\', "/"))
+    }
+
+    fn variable(&self, source: &str, name: &str) -> Result<String, ExpandPathError> {
+        match (self.lookup)(name) {
+            Some(value) => Ok(value),
+            None => Err(ExpandPathError::new(source, name)),
+        }
+    }
+
+    fn known_folder(&self, source: &str, name: &str) -> Result<String, ExpandPathError> {
+        let candidates =
+            known_folder_variables(name).ok_or_else(|| ExpandPathError::new(source, name))?;
+        candidates
+            .iter()
+            .find_map(|candidate| (self.lookup)(candidate))
+            .ok_or_else(|| ExpandPathError::new(source, name))
+    }
+}
+
+// environment variables a known folder is read from, the first one set wins
+fn known_folder_variables(name: &str) -> Option<&'static [&'static str]> {
+    let candidates: &[&str] = match name.to_ascii_uppercase().as_str() {
+        "HOME" => &["USERPROFILE", "HOME"],
+        "APPDATA" => &["APPDATA"],
+        "LOCALAPPDATA" => &["LOCALAPPDATA"],
+        "PROGRAMDATA" => &["ProgramData"],
+        "PROGRAMFILES" => &["ProgramFiles"],
+        "PROGRAMFILESX86" => &["ProgramFiles(x86)"],
+        "TEMP" => &["TEMP", "TMPDIR"],
+        _ => return None,
+    };
+    Some(candidates)
+}
+
+// split "<open>name<close>rest" into name and rest, name must be a plain identifier
+fn enclosed<'a>(source: &'a str, open: &str, close: &str) -> Option<(&'a str, &'a str)> {
+    let inner = source.strip_prefix(open)?;
+    let end = inner.find(close)?;
+    let name = &inner[..end];
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '(' || c == ')');
+    if valid {
+        Some((name, &inner[end + close.len()..]))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExpandPathError, PathExpander};
+
+    fn expander() -> PathExpander {
+        PathExpander::new(Box::new(|name| match name {
+            "USERPROFILE" => Some("C:\\Users\\alice".to_string()),
+            "LOCALAPPDATA" => Some("C:\\Users\\alice\\AppData\\Local".to_string()),
+            "ProgramFiles" => Some("C:\\Program Files".to_string()),
+            "ProgramFiles(x86)" => Some("C:\\Program Files (x86)".to_string()),
+            "EDITOR" => Some("code".to_string()),
+            _ => None,
+        }))
+    }
+
+    #[test]
+    fn it_works() {
+        let expander = expander();
+        assert_eq!(
+            expander
+                .expand_path("%LOCALAPPDATA%\\Discord\\Update.exe")
+                .unwrap(),
+            "C:/Users/alice/AppData/Local/Discord/Update.exe"
+        );
+        assert_eq!(expander.expand("${EDITOR}.exe").unwrap(), "code.exe");
+        assert_eq!(
+            expander.expand_path("~/bin/app.exe").unwrap(),
+            "C:/Users/alice/bin/app.exe"
+        );
+        assert_eq!(
+            expander
+                .expand_path("{ProgramFiles}/Git/git-bash.exe")
+                .unwrap(),
+            "C:/Program Files/Git/git-bash.exe"
+        );
+        assert_eq!(
+            expander.expand_path("{programfilesx86}/app.exe").unwrap(),
+            "C:/Program Files (x86)/app.exe"
+        );
+
+        // commands keep their backslashes, only variables are replaced
+        assert_eq!(
+            expander
+                .expand(r#"%EDITOR% -e "a\"b" \\server\share"#)
+                .unwrap(),
+            r#"code -e "a\"b" \\server\share"#
+        );
+    }
+
+    #[test]
+    fn literal() {
+        let expander = expander();
+        assert_eq!(
+            expander.expand("C:/ProgramFile/test.exe").unwrap(),
+            "C:/ProgramFile/test.exe"
+        );
+        assert_eq!(expander.expand("100%% $5 {a b}").unwrap(), "100% $5 {a b}");
+        assert_eq!(expander.expand("a~b").unwrap(), "a~b");
+        // braces which are no known folder
+        assert_eq!(
+            expander.expand("{Desktop}/app-{v2}/{0}.exe").unwrap(),
+            "{Desktop}/app-{v2}/{0}.exe"
+        );
+    }
+
+    #[test]
+    fn unknown() {
+        let expander = expander();
+        let source = "%MISSING%/app.exe";
+        assert_eq!(
+            expander.expand(source),
+            Err(ExpandPathError::new(source, "MISSING"))
+        );
+        // known folder without its variable
+        let source = "{AppData}/app.exe";
+        assert_eq!(
+            expander.expand(source),
+            Err(ExpandPathError::new(source, "AppData"))
+        );
+    }
+}
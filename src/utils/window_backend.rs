@@ -0,0 +1,220 @@
+use std::{io, process::Command};
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, WPARAM},
+    UI::{
+        Input::KeyboardAndMouse::{
+            SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS,
+            KEYEVENTF_KEYUP, VIRTUAL_KEY,
+        },
+        WindowsAndMessaging::{
+            PostMessageW, SetForegroundWindow, ShowWindow, SW_MAXIMIZE, SW_MINIMIZE, WM_CLOSE,
+        },
+    },
+};
+
+use super::{
+    FrontendFilter, Hotkey, Rect, WindowEvent, WindowEventHook, WindowFilter, WindowFinder,
+    WindowInfo, WindowSnapshot, WindowState,
+};
+
+// everything the action executor needs from the window system,
+// implemented with win32 and by a fake backend for tests
+pub trait WindowBackend {
+    // windows kept by filter, in z-order from top to bottom
+    fn get_windows_with(&self, filter: &FrontendFilter) -> Vec<HWND>;
+    // window would be kept by filter when windows are enumerated
+    fn is_eligible(&self, hwnd: HWND, filter: &FrontendFilter) -> bool;
+    fn get_pid(&self, hwnd: HWND) -> u32;
+    fn get_process_name(&self, hwnd: HWND) -> String;
+    fn get_title(&self, hwnd: HWND) -> String;
+    fn get_class(&self, hwnd: HWND) -> String;
+    fn get_monitor(&self, hwnd: HWND) -> u32;
+    fn get_rect(&self, hwnd: HWND) -> Rect;
+    fn get_owner(&self, hwnd: HWND) -> HWND;
+    fn get_foreground_window(&self) -> HWND;
+    fn get_state(&self, hwnd: HWND) -> WindowState;
+    // bring a minimized window back to the placement it had before minimizing
+    fn restore(&mut self, hwnd: HWND);
+    // restores the window if needed
+    fn focus(&mut self, hwnd: HWND);
+    fn close(&mut self, hwnd: HWND);
+    fn minimize(&mut self, hwnd: HWND);
+    fn maximize(&mut self, hwnd: HWND);
+    // start a process, returns its pid
+    fn spawn(&mut self, command: Command) -> io::Result<u32>;
+    fn send_keys(&mut self, keys: &[Hotkey]);
+    // changes of windows since the last call, in the order they happened
+    fn take_events(&mut self) -> Vec<WindowEvent>;
+
+    // windows which can be switched to
+    fn get_windows(&self) -> Vec<HWND> {
+        self.get_windows_with(&FrontendFilter::default())
+    }
+
+    fn is_minimized(&self, hwnd: HWND) -> bool {
+        self.get_state(hwnd) == WindowState::Minimized
+    }
+
+    fn describe(&self, hwnd: HWND) -> WindowInfo {
+        let state = self.get_state(hwnd);
+        WindowInfo {
+            hwnd,
+            title: self.get_title(hwnd),
+            class: self.get_class(hwnd),
+            pid: self.get_pid(hwnd),
+            path: self.get_process_name(hwnd),
+            rect: self.get_rect(hwnd),
+            monitor: self.get_monitor(hwnd),
+            state,
+            z_order: 0,
+            owner: self.get_owner(hwnd),
+            visible: state != WindowState::Hidden,
+            cloaked: state == WindowState::Cloaked,
+        }
+    }
+
+    // every switchable window described in z-order
+    fn snapshot(&self) -> WindowSnapshot {
+        self.snapshot_with(&FrontendFilter::default())
+    }
+
+    fn snapshot_with(&self, filter: &FrontendFilter) -> WindowSnapshot {
+        self.get_windows_with(filter)
+            .into_iter()
+            .enumerate()
+            .map(|(z_order, hwnd)| WindowInfo {
+                z_order,
+                ..self.describe(hwnd)
+            })
+            .collect()
+    }
+}
+
+pub struct Win32Backend;
+
+impl WindowBackend for Win32Backend {
+    fn get_windows_with(&self, filter: &FrontendFilter) -> Vec<HWND> {
+        WindowFinder::find_windows(*filter)
+    }
+
+    fn is_eligible(&self, hwnd: HWND, filter: &FrontendFilter) -> bool {
+        let mut filter = *filter;
+        let candidate = WindowFinder::get_window_candidate(hwnd, filter.needs_cloaked());
+        filter.is_match(&candidate)
+    }
+
+    fn get_pid(&self, hwnd: HWND) -> u32 {
+        WindowFinder::get_process_id_from_hwnd(hwnd)
+    }
+
+    fn get_process_name(&self, hwnd: HWND) -> String {
+        WindowFinder::get_process_name_from_hwnd(hwnd)
+    }
+
+    fn get_title(&self, hwnd: HWND) -> String {
+        WindowFinder::get_window_title(hwnd)
+    }
+
+    fn get_class(&self, hwnd: HWND) -> String {
+        WindowFinder::get_window_class(hwnd)
+    }
+
+    fn get_monitor(&self, hwnd: HWND) -> u32 {
+        WindowFinder::get_window_monitor(hwnd)
+    }
+
+    fn get_rect(&self, hwnd: HWND) -> Rect {
+        let rect = WindowFinder::get_window_info(hwnd).rcWindow;
+        Rect::new(rect.left, rect.top, rect.right, rect.bottom)
+    }
+
+    fn get_owner(&self, hwnd: HWND) -> HWND {
+        WindowFinder::get_window_owner(hwnd)
+    }
+
+    fn get_foreground_window(&self) -> HWND {
+        WindowFinder::get_foreground_window()
+    }
+
+    fn get_state(&self, hwnd: HWND) -> WindowState {
+        WindowState::from_placement(&WindowFinder::get_window_placement(hwnd))
+    }
+
+    fn restore(&mut self, hwnd: HWND) {
+        WindowFinder::restore_window(hwnd);
+    }
+
+    fn describe(&self, hwnd: HWND) -> WindowInfo {
+        WindowFinder::describe_window(hwnd)
+    }
+
+    fn snapshot_with(&self, filter: &FrontendFilter) -> WindowSnapshot {
+        WindowSnapshot::new(WindowFinder::get_window_info_with(*filter))
+    }
+
+    fn focus(&mut self, hwnd: HWND) {
+        self.restore(hwnd);
+        unsafe {
+            SetForegroundWindow(hwnd);
+        }
+    }
+
+    fn close(&mut self, hwnd: HWND) {
+        unsafe {
+            PostMessageW(hwnd, WM_CLOSE, WPARAM(0), LPARAM(0));
+        }
+    }
+
+    fn minimize(&mut self, hwnd: HWND) {
+        unsafe {
+            ShowWindow(hwnd, SW_MINIMIZE);
+        }
+    }
+
+    fn maximize(&mut self, hwnd: HWND) {
+        unsafe {
+            ShowWindow(hwnd, SW_MAXIMIZE);
+        }
+    }
+
+    fn take_events(&mut self) -> Vec<WindowEvent> {
+        WindowEventHook::take_events()
+    }
+
+    fn spawn(&mut self, mut command: Command) -> io::Result<u32> {
+        command.spawn().map(|child| child.id())
+    }
+
+    fn send_keys(&mut self, keys: &[Hotkey]) {
+        let input = |key: u16, flags: KEYBD_EVENT_FLAGS| INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: VIRTUAL_KEY(key),
+                    wScan: 0,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        };
+        for hotkey in keys.iter() {
+            let virtual_keys = hotkey.get_virtual_keys();
+            // press in order and release in reverse order
+            let inputs: Vec<INPUT> = virtual_keys
+                .iter()
+                .map(|key| input(*key, KEYBD_EVENT_FLAGS(0)))
+                .chain(
+                    virtual_keys
+                        .iter()
+                        .rev()
+                        .map(|key| input(*key, KEYEVENTF_KEYUP)),
+                )
+                .collect();
+            unsafe {
+                SendInput(&inputs, std::mem::size_of::<INPUT>() as i32);
+            }
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::{cell::RefCell, collections::VecDeque};
+
+use windows::Win32::{
+    Foundation::{HINSTANCE, HWND},
+    UI::{
+        Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK},
+        WindowsAndMessaging::{
+            GetAncestor, CHILDID_SELF, EVENT_OBJECT_CLOAKED, EVENT_OBJECT_CREATE,
+            EVENT_OBJECT_DESTROY, EVENT_OBJECT_HIDE, EVENT_OBJECT_LOCATIONCHANGE,
+            EVENT_OBJECT_NAMECHANGE, EVENT_OBJECT_UNCLOAKED, EVENT_SYSTEM_FOREGROUND,
+            EVENT_SYSTEM_MINIMIZEEND, EVENT_SYSTEM_MINIMIZESTART, GA_ROOT, OBJID_WINDOW,
+            WINEVENT_OUTOFCONTEXT, WINEVENT_SKIPOWNPROCESS,
+        },
+    },
+};
+
+// what happened to a window, reported by the window system
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowEvent {
+    Created(HWND),
+    Destroyed(HWND),
+    Foreground(HWND),
+    TitleChanged(HWND),
+    // shown, hidden, minimized, restored, maximized, moved, resized, cloaked
+    // or uncloaked, queued once until taken
+    StateChanged(HWND),
+    // events were lost, everything has to be enumerated again
+    Resync,
+}
+
+// only the win event hooks of windows feed the queue, an X11 source watching
+// PropertyNotify of _NET_ACTIVE_WINDOW and _NET_CLIENT_LIST is left for when
+// ektox has an X11 window backend, until then nothing is switched to there
+
+// events kept until they are taken, older ones are dropped for a resync
+const MAX_QUEUED_EVENTS: usize = 1024;
+
+thread_local! {
+    // hooks are called on the thread which installed them, from its message loop
+    static EVENTS: RefCell<VecDeque<WindowEvent>> = const { RefCell::new(VecDeque::new()) };
+}
+
+// win event hooks of the calling thread, removed when dropped
+pub struct WindowEventHook {
+    hooks: Vec<HWINEVENTHOOK>,
+}
+
+impl WindowEventHook {
+    pub fn install() -> Self {
+        let ranges = [
+            (EVENT_OBJECT_CREATE, EVENT_OBJECT_HIDE),
+            (EVENT_OBJECT_NAMECHANGE, EVENT_OBJECT_NAMECHANGE),
+            (EVENT_OBJECT_LOCATIONCHANGE, EVENT_OBJECT_LOCATIONCHANGE),
+            (EVENT_OBJECT_CLOAKED, EVENT_OBJECT_UNCLOAKED),
+            (EVENT_SYSTEM_FOREGROUND, EVENT_SYSTEM_FOREGROUND),
+            (EVENT_SYSTEM_MINIMIZESTART, EVENT_SYSTEM_MINIMIZEEND),
+        ];
+        let hooks = ranges
+            .iter()
+            .map(|(min, max)| unsafe {
+                SetWinEventHook(
+                    *min,
+                    *max,
+                    HINSTANCE(0),
+                    Some(Self::event_proc),
+                    0,
+                    0,
+                    WINEVENT_OUTOFCONTEXT | WINEVENT_SKIPOWNPROCESS,
+                )
+            })
+            .filter(|hook| hook.0 != 0)
+            .collect();
+        WindowEventHook { hooks }
+    }
+
+    unsafe extern "system" fn event_proc(
+        _hook: HWINEVENTHOOK,
+        event: u32,
+        hwnd: HWND,
+        id_object: i32,
+        id_child: i32,
+        _thread: u32,
+        _time: u32,
+    ) {
+        // only top level windows themselves, not their controls
+        if id_object != OBJID_WINDOW.0 || id_child != CHILDID_SELF as i32 || hwnd.0 == 0 {
+            return;
+        }
+        // location changes come for every control moving too
+        if event == EVENT_OBJECT_LOCATIONCHANGE && GetAncestor(hwnd, GA_ROOT) != hwnd {
+            return;
+        }
+        let event = match event {
+            EVENT_OBJECT_CREATE => WindowEvent::Created(hwnd),
+            EVENT_OBJECT_DESTROY => WindowEvent::Destroyed(hwnd),
+            EVENT_SYSTEM_FOREGROUND => WindowEvent::Foreground(hwnd),
+            EVENT_OBJECT_NAMECHANGE => WindowEvent::TitleChanged(hwnd),
+            _ => WindowEvent::StateChanged(hwnd),
+        };
+        Self::push(event);
+    }
+
+    pub fn push(event: WindowEvent) {
+        EVENTS.with(|events| {
+            let mut events = events.borrow_mut();
+            if events.len() >= MAX_QUEUED_EVENTS {
+                events.clear();
+                events.push_back(WindowEvent::Resync);
+            }
+            // a window moved or resized sends a state change for every step
+            let queued = matches!(event, WindowEvent::StateChanged(_)) && events.contains(&event);
+            if events.front() != Some(&WindowEvent::Resync) && !queued {
+                events.push_back(event);
+            }
+        });
+    }
+
+    // events of the calling thread in the order they happened
+    pub fn take_events() -> Vec<WindowEvent> {
+        EVENTS.with(|events| events.borrow_mut().drain(..).collect())
+    }
+}
+
+impl Drop for WindowEventHook {
+    fn drop(&mut self) {
+        for hook in self.hooks.iter() {
+            unsafe {
+                UnhookWinEvent(*hook);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use windows::Win32::Foundation::HWND;
+
+    use super::{WindowEvent, WindowEventHook, MAX_QUEUED_EVENTS};
+
+    #[test]
+    fn it_works() {
+        WindowEventHook::push(WindowEvent::Created(HWND(1)));
+        WindowEventHook::push(WindowEvent::Foreground(HWND(1)));
+        assert_eq!(
+            WindowEventHook::take_events(),
+            vec![
+                WindowEvent::Created(HWND(1)),
+                WindowEvent::Foreground(HWND(1))
+            ]
+        );
+        assert!(WindowEventHook::take_events().is_empty());
+
+        // state changes of a window are queued once
+        WindowEventHook::push(WindowEvent::StateChanged(HWND(1)));
+        WindowEventHook::push(WindowEvent::StateChanged(HWND(2)));
+        WindowEventHook::push(WindowEvent::StateChanged(HWND(1)));
+        assert_eq!(
+            WindowEventHook::take_events(),
+            vec![
+                WindowEvent::StateChanged(HWND(1)),
+                WindowEvent::StateChanged(HWND(2))
+            ]
+        );
+
+        // too many events are replaced by a resync
+        for id in 0..MAX_QUEUED_EVENTS + 10 {
+            WindowEventHook::push(WindowEvent::TitleChanged(HWND(id as isize)));
+        }
+        assert_eq!(WindowEventHook::take_events(), vec![WindowEvent::Resync]);
+    }
+}
@@ -0,0 +1,348 @@
+use windows::Win32::{
+    Foundation::HWND,
+    UI::WindowsAndMessaging::{WS_EX_TOOLWINDOW, WS_POPUP, WS_VISIBLE},
+};
+
+use super::Rect;
+
+// what filters look at while windows are enumerated, read once per window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WindowCandidate {
+    pub hwnd: HWND,
+    pub owner: HWND,
+    pub title_len: usize,
+    pub style: u32,
+    pub ex_style: u32,
+    pub rect: Rect,
+    pub cloaked: bool,
+}
+
+// decides which enumerated windows are kept, closures taking a candidate are filters too
+pub trait WindowFilter {
+    fn is_match(&mut self, window: &WindowCandidate) -> bool;
+
+    // whether is_match looks at `cloaked`, which is costly to read, it's
+    // left false in candidates of filters which do not
+    fn needs_cloaked(&self) -> bool {
+        true
+    }
+
+    fn and<F: WindowFilter>(self, other: F) -> And<Self, F>
+    where
+        Self: Sized,
+    {
+        And(self, other)
+    }
+
+    fn or<F: WindowFilter>(self, other: F) -> Or<Self, F>
+    where
+        Self: Sized,
+    {
+        Or(self, other)
+    }
+
+    fn not(self) -> Not<Self>
+    where
+        Self: Sized,
+    {
+        Not(self)
+    }
+
+    fn boxed(self) -> Box<dyn WindowFilter>
+    where
+        Self: Sized + 'static,
+    {
+        Box::new(self)
+    }
+}
+
+impl<F: FnMut(&WindowCandidate) -> bool> WindowFilter for F {
+    fn is_match(&mut self, window: &WindowCandidate) -> bool {
+        self(window)
+    }
+}
+
+impl WindowFilter for Box<dyn WindowFilter> {
+    fn is_match(&mut self, window: &WindowCandidate) -> bool {
+        self.as_mut().is_match(window)
+    }
+
+    fn needs_cloaked(&self) -> bool {
+        self.as_ref().needs_cloaked()
+    }
+}
+
+pub struct And<A, B>(A, B);
+
+impl<A: WindowFilter, B: WindowFilter> WindowFilter for And<A, B> {
+    fn is_match(&mut self, window: &WindowCandidate) -> bool {
+        self.0.is_match(window) && self.1.is_match(window)
+    }
+
+    fn needs_cloaked(&self) -> bool {
+        self.0.needs_cloaked() || self.1.needs_cloaked()
+    }
+}
+
+pub struct Or<A, B>(A, B);
+
+impl<A: WindowFilter, B: WindowFilter> WindowFilter for Or<A, B> {
+    fn is_match(&mut self, window: &WindowCandidate) -> bool {
+        self.0.is_match(window) || self.1.is_match(window)
+    }
+
+    fn needs_cloaked(&self) -> bool {
+        self.0.needs_cloaked() || self.1.needs_cloaked()
+    }
+}
+
+pub struct Not<A>(A);
+
+impl<A: WindowFilter> WindowFilter for Not<A> {
+    fn is_match(&mut self, window: &WindowCandidate) -> bool {
+        !self.0.is_match(window)
+    }
+
+    fn needs_cloaked(&self) -> bool {
+        self.0.needs_cloaked()
+    }
+}
+
+// every window
+pub struct AnyWindow;
+
+impl WindowFilter for AnyWindow {
+    fn is_match(&mut self, _window: &WindowCandidate) -> bool {
+        true
+    }
+
+    fn needs_cloaked(&self) -> bool {
+        false
+    }
+}
+
+// not owned by another window, like dialogs are
+pub struct NoOwner;
+
+impl WindowFilter for NoOwner {
+    fn is_match(&mut self, window: &WindowCandidate) -> bool {
+        window.owner == HWND(0)
+    }
+
+    fn needs_cloaked(&self) -> bool {
+        false
+    }
+}
+
+pub struct HasTitle;
+
+impl WindowFilter for HasTitle {
+    fn is_match(&mut self, window: &WindowCandidate) -> bool {
+        window.title_len > 0
+    }
+
+    fn needs_cloaked(&self) -> bool {
+        false
+    }
+}
+
+pub struct NotPopup;
+
+impl WindowFilter for NotPopup {
+    fn is_match(&mut self, window: &WindowCandidate) -> bool {
+        window.style & WS_POPUP.0 == 0
+    }
+
+    fn needs_cloaked(&self) -> bool {
+        false
+    }
+}
+
+pub struct Visible;
+
+impl WindowFilter for Visible {
+    fn is_match(&mut self, window: &WindowCandidate) -> bool {
+        window.style & WS_VISIBLE.0 != 0
+    }
+
+    fn needs_cloaked(&self) -> bool {
+        false
+    }
+}
+
+// not a floating tool window
+pub struct NotToolWindow;
+
+impl WindowFilter for NotToolWindow {
+    fn is_match(&mut self, window: &WindowCandidate) -> bool {
+        window.ex_style & WS_EX_TOOLWINDOW.0 == 0
+    }
+
+    fn needs_cloaked(&self) -> bool {
+        false
+    }
+}
+
+// shown by the compositor, not on another virtual desktop
+pub struct NotCloaked;
+
+impl WindowFilter for NotCloaked {
+    fn is_match(&mut self, window: &WindowCandidate) -> bool {
+        !window.cloaked
+    }
+}
+
+// width and height are both at least this many pixels
+pub struct MinSize(pub i32);
+
+impl WindowFilter for MinSize {
+    fn is_match(&mut self, window: &WindowCandidate) -> bool {
+        window.rect.get_width() >= self.0 && window.rect.get_height() >= self.0
+    }
+
+    fn needs_cloaked(&self) -> bool {
+        false
+    }
+}
+
+// heuristics telling windows a user would switch to from everything else,
+// each of them can be turned off, cloaked windows are kept unless asked for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrontendFilter {
+    pub no_owner: bool,
+    pub has_title: bool,
+    pub not_popup: bool,
+    pub visible: bool,
+    pub not_tool_window: bool,
+    pub not_cloaked: bool,
+    pub min_size: Option<i32>,
+}
+
+impl Default for FrontendFilter {
+    fn default() -> Self {
+        FrontendFilter {
+            no_owner: true,
+            has_title: true,
+            not_popup: true,
+            visible: true,
+            not_tool_window: true,
+            not_cloaked: false,
+            min_size: Some(2),
+        }
+    }
+}
+
+impl WindowFilter for FrontendFilter {
+    fn is_match(&mut self, window: &WindowCandidate) -> bool {
+        (!self.no_owner || NoOwner.is_match(window))
+            && (!self.has_title || HasTitle.is_match(window))
+            && (!self.not_popup || NotPopup.is_match(window))
+            && (!self.visible || Visible.is_match(window))
+            && (!self.not_tool_window || NotToolWindow.is_match(window))
+            && (!self.not_cloaked || NotCloaked.is_match(window))
+            && self
+                .min_size
+                .is_none_or(|size| MinSize(size).is_match(window))
+    }
+
+    fn needs_cloaked(&self) -> bool {
+        self.not_cloaked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use windows::Win32::{
+        Foundation::HWND,
+        UI::WindowsAndMessaging::{WS_EX_TOOLWINDOW, WS_POPUP, WS_VISIBLE},
+    };
+
+    use crate::utils::Rect;
+
+    use super::{
+        FrontendFilter, HasTitle, NoOwner, NotCloaked, NotPopup, WindowCandidate, WindowFilter,
+    };
+
+    fn candidate(id: isize) -> WindowCandidate {
+        WindowCandidate {
+            hwnd: HWND(id),
+            owner: HWND(0),
+            title_len: 5,
+            style: WS_VISIBLE.0,
+            ex_style: 0,
+            rect: Rect::new(0, 0, 800, 600),
+            cloaked: false,
+        }
+    }
+
+    #[test]
+    fn it_works() {
+        let mut filter = FrontendFilter::default();
+        assert!(filter.is_match(&candidate(1)));
+        let owned = WindowCandidate {
+            owner: HWND(1),
+            ..candidate(2)
+        };
+        assert!(!filter.is_match(&owned));
+        let tool = WindowCandidate {
+            ex_style: WS_EX_TOOLWINDOW.0,
+            ..candidate(3)
+        };
+        assert!(!filter.is_match(&tool));
+        let tiny = WindowCandidate {
+            rect: Rect::new(0, 0, 1, 1),
+            ..candidate(4)
+        };
+        assert!(!filter.is_match(&tiny));
+
+        // owned dialogs are kept once the owner rule is off
+        let mut dialogs = FrontendFilter {
+            no_owner: false,
+            ..Default::default()
+        };
+        assert!(dialogs.is_match(&owned));
+        assert!(!dialogs.is_match(&tool));
+
+        // cloaked windows are only dropped on request
+        let cloaked = WindowCandidate {
+            cloaked: true,
+            ..candidate(5)
+        };
+        assert!(filter.is_match(&cloaked));
+        assert!(!filter.needs_cloaked());
+        let mut visible_only = FrontendFilter {
+            not_cloaked: true,
+            ..Default::default()
+        };
+        assert!(!visible_only.is_match(&cloaked));
+        assert!(visible_only.needs_cloaked());
+        assert!(!NoOwner.and(HasTitle).needs_cloaked());
+        assert!(NoOwner.or(NotCloaked).needs_cloaked());
+    }
+
+    #[test]
+    fn combinators() {
+        let popup = WindowCandidate {
+            style: WS_VISIBLE.0 | WS_POPUP.0,
+            ..candidate(1)
+        };
+        let untitled = WindowCandidate {
+            title_len: 0,
+            ..candidate(2)
+        };
+        assert!(!NotPopup.and(HasTitle).is_match(&popup));
+        assert!(NotPopup.or(HasTitle).is_match(&popup));
+        assert!(HasTitle.not().is_match(&untitled));
+        assert!(NoOwner.and(HasTitle.or(NotPopup)).is_match(&untitled));
+
+        // closures capture state
+        let mut seen = Vec::new();
+        let mut filter = NoOwner.and(|window: &WindowCandidate| {
+            seen.push(window.hwnd);
+            window.hwnd.0 % 2 == 0
+        });
+        assert!(!filter.is_match(&popup));
+        assert!(filter.is_match(&untitled));
+        assert_eq!(seen, vec![HWND(1), HWND(2)]);
+    }
+}
@@ -1,39 +1,81 @@
-use windows::Win32::{
-    Foundation::{CloseHandle, BOOL, HINSTANCE, HWND, LPARAM, MAX_PATH},
-    System::{
-        ProcessStatus::K32GetModuleFileNameExW,
-        Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION},
-    },
-    UI::WindowsAndMessaging::{
-        EnumWindows, GetForegroundWindow, GetWindow, GetWindowInfo, GetWindowLongW,
-        GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId, GWL_EXSTYLE, GWL_STYLE,
-        GW_OWNER, WINDOWINFO, WS_EX_TOOLWINDOW, WS_POPUP, WS_VISIBLE,
+use windows::{
+    core::PWSTR,
+    Win32::{
+        Foundation::{
+            CloseHandle, GetLastError, BOOL, ERROR_INSUFFICIENT_BUFFER, HWND, LPARAM, MAX_PATH,
+            RECT,
+        },
+        Graphics::{
+            Dwm::{DwmGetWindowAttribute, DWMWA_CLOAKED},
+            Gdi::{
+                EnumDisplayMonitors, GetMonitorInfoW, MonitorFromWindow, HDC, HMONITOR,
+                MONITORINFO, MONITOR_DEFAULTTONEAREST,
+            },
+        },
+        System::Threading::{
+            OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+            PROCESS_QUERY_LIMITED_INFORMATION,
+        },
+        UI::WindowsAndMessaging::{
+            EnumWindows, GetClassNameW, GetForegroundWindow, GetWindow, GetWindowInfo,
+            GetWindowLongW, GetWindowPlacement, GetWindowTextLengthW, GetWindowTextW,
+            GetWindowThreadProcessId, IsIconic, IsWindowVisible, IsZoomed, ShowWindow, GWL_EXSTYLE,
+            GWL_STYLE, GW_OWNER, SW_RESTORE, SW_SHOWMAXIMIZED, WINDOWINFO, WINDOWPLACEMENT,
+            WPF_RESTORETOMAXIMIZED, WS_CAPTION, WS_EX_TOOLWINDOW, WS_THICKFRAME,
+        },
     },
 };
 
-pub type EnumWindowPredicate = fn(&EnumWindowParam, HWND) -> bool;
+use super::{
+    FrontendFilter, Rect, WindowCandidate, WindowFilter, WindowInfo, WindowPlacement, WindowState,
+};
+
+// state of one EnumWindows call, enumeration stops once `continue_enum` is cleared
 pub struct EnumWindowParam {
-    predicate: EnumWindowPredicate,
+    filter: Box<dyn WindowFilter>,
     window_handles: Vec<HWND>,
+    // stop after this many windows were kept
+    limit: Option<usize>,
     continue_enum: bool,
 }
 
 impl EnumWindowParam {
-    pub fn new(predicate: EnumWindowPredicate) -> Self {
+    pub fn new<F: WindowFilter + 'static>(filter: F) -> Self {
         EnumWindowParam {
-            predicate,
+            filter: filter.boxed(),
             window_handles: Vec::<HWND>::new(),
+            limit: None,
             continue_enum: true,
         }
     }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
     pub fn clear(&mut self) {
         self.window_handles.clear();
     }
 
-    pub fn filter(&mut self, hwnd: HWND) -> bool {
-        let result = (self.predicate)(self, hwnd);
+    pub fn stop(&mut self) {
+        self.continue_enum = false;
+    }
+
+    pub fn get_window_handles(&self) -> &Vec<HWND> {
+        &self.window_handles
+    }
+
+    pub fn filter(&mut self, window: &WindowCandidate) -> bool {
+        let result = self.filter.is_match(window);
         if result {
-            self.window_handles.push(hwnd);
+            self.window_handles.push(window.hwnd);
+            if self
+                .limit
+                .is_some_and(|limit| self.window_handles.len() >= limit)
+            {
+                self.stop();
+            }
         }
         result
     }
@@ -43,9 +85,10 @@ pub struct WindowFinder;
 impl WindowFinder {
     pub fn enum_window(enum_window_param: &mut EnumWindowParam) {
         unsafe extern "system" fn enum_window_proc(hwnd: HWND, lparam: LPARAM) -> BOOL {
-            let enum_window_param: &mut EnumWindowParam = std::mem::transmute(lparam.0);
+            let enum_window_param = &mut *(lparam.0 as *mut EnumWindowParam);
             // filter window handle
-            enum_window_param.filter(hwnd);
+            let cloaked = enum_window_param.filter.needs_cloaked();
+            enum_window_param.filter(&WindowFinder::get_window_candidate(hwnd, cloaked));
             BOOL::from(enum_window_param.continue_enum)
         }
 
@@ -54,16 +97,189 @@ impl WindowFinder {
         enum_window_param.clear();
 
         unsafe {
-            let pointer: isize = std::mem::transmute(enum_window_param);
+            let pointer = enum_window_param as *mut EnumWindowParam as isize;
             EnumWindows(Some(enum_window_proc), LPARAM(pointer));
         }
     }
 
+    // title may change between reading its length and the text, a full buffer is read again,
+    // the buffer has room for the title, its terminator and one more character to tell
+    // a title that fits from a truncated one
     pub fn get_window_title(hwnd: HWND) -> String {
-        static mut BUFFER: [u16; 256] = [0; 256];
+        let len = unsafe { GetWindowTextLengthW(hwnd) } as usize;
+        read_wide_string(len + 2, |buffer| {
+            let len = unsafe { GetWindowTextW(hwnd, buffer) } as usize;
+            if len + 1 < buffer.len() {
+                Some(len)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn get_window_class(hwnd: HWND) -> String {
+        let mut buffer = [0u16; 256];
+        unsafe {
+            let len = GetClassNameW(hwnd, &mut buffer) as usize;
+            String::from_utf16_lossy(&buffer[0..len])
+        }
+    }
+
+    fn get_monitors() -> Vec<HMONITOR> {
+        unsafe extern "system" fn enum_monitor_proc(
+            monitor: HMONITOR,
+            _hdc: HDC,
+            _rect: *mut RECT,
+            lparam: LPARAM,
+        ) -> BOOL {
+            let monitors = &mut *(lparam.0 as *mut Vec<HMONITOR>);
+            monitors.push(monitor);
+            BOOL::from(true)
+        }
+        let mut monitors: Vec<HMONITOR> = Vec::new();
+        unsafe {
+            EnumDisplayMonitors(
+                HDC::default(),
+                std::ptr::null(),
+                Some(enum_monitor_proc),
+                LPARAM(&mut monitors as *mut Vec<HMONITOR> as isize),
+            );
+        }
+        monitors
+    }
+
+    fn get_monitor_index(hwnd: HWND, monitors: &[HMONITOR]) -> u32 {
+        let monitor = unsafe { MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST) };
+        monitors
+            .iter()
+            .position(|m| *m == monitor)
+            .map(|index| index as u32 + 1)
+            .unwrap_or(0)
+    }
+
+    // 1-based index of the monitor in enumeration order, 0 if unknown
+    pub fn get_window_monitor(hwnd: HWND) -> u32 {
+        Self::get_monitor_index(hwnd, &Self::get_monitors())
+    }
+
+    pub fn get_window_owner(hwnd: HWND) -> HWND {
+        unsafe { GetWindow(hwnd, GW_OWNER) }
+    }
+
+    fn get_placement(hwnd: HWND) -> WINDOWPLACEMENT {
+        let mut placement = WINDOWPLACEMENT {
+            length: std::mem::size_of::<WINDOWPLACEMENT>() as u32,
+            ..Default::default()
+        };
+        unsafe {
+            GetWindowPlacement(hwnd, &mut placement);
+        }
+        placement
+    }
+
+    pub fn is_window_cloaked(hwnd: HWND) -> bool {
+        let mut cloaked: u32 = 0;
+        unsafe {
+            DwmGetWindowAttribute(
+                hwnd,
+                DWMWA_CLOAKED,
+                &mut cloaked as *mut u32 as *mut std::ffi::c_void,
+                std::mem::size_of::<u32>() as u32,
+            )
+            .is_ok()
+                && cloaked != 0
+        }
+    }
+
+    pub fn get_window_placement(hwnd: HWND) -> WindowPlacement {
+        Self::get_placement_from(hwnd, &Self::get_window_info(hwnd))
+    }
+
+    fn get_placement_from(hwnd: HWND, window_info: &WINDOWINFO) -> WindowPlacement {
+        let placement = Self::get_placement(hwnd);
+        let mut monitor_info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        unsafe {
+            GetMonitorInfoW(
+                MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST),
+                &mut monitor_info,
+            );
+        }
+        // restored position is in work area coordinates, unless the window is a tool window
+        let to_rect = |rect: RECT| Rect::new(rect.left, rect.top, rect.right, rect.bottom);
+        let mut normal_rect = placement.rcNormalPosition;
+        if window_info.dwExStyle & WS_EX_TOOLWINDOW.0 == 0 {
+            let dx = monitor_info.rcWork.left - monitor_info.rcMonitor.left;
+            let dy = monitor_info.rcWork.top - monitor_info.rcMonitor.top;
+            normal_rect.left += dx;
+            normal_rect.right += dx;
+            normal_rect.top += dy;
+            normal_rect.bottom += dy;
+        }
+        unsafe {
+            WindowPlacement {
+                visible: IsWindowVisible(hwnd).as_bool(),
+                cloaked: Self::is_window_cloaked(hwnd),
+                minimized: IsIconic(hwnd).as_bool(),
+                maximized: IsZoomed(hwnd).as_bool(),
+                framed: window_info.dwStyle & (WS_CAPTION.0 | WS_THICKFRAME.0) != 0,
+                rect: to_rect(window_info.rcWindow),
+                normal_rect: to_rect(normal_rect),
+                monitor_rect: to_rect(monitor_info.rcMonitor),
+            }
+        }
+    }
+
+    // everything about a window, each piece queried once
+    pub fn describe_window(hwnd: HWND) -> WindowInfo {
+        Self::describe_window_with(hwnd, &Self::get_monitors(), 0)
+    }
+
+    fn describe_window_with(hwnd: HWND, monitors: &[HMONITOR], z_order: usize) -> WindowInfo {
+        let window_info = Self::get_window_info(hwnd);
+        let placement = Self::get_placement_from(hwnd, &window_info);
+        let pid = Self::get_process_id_from_hwnd(hwnd);
+        WindowInfo {
+            hwnd,
+            title: Self::get_window_title(hwnd),
+            class: Self::get_window_class(hwnd),
+            pid,
+            path: Self::get_process_name_from_pid(pid),
+            rect: placement.rect,
+            monitor: Self::get_monitor_index(hwnd, monitors),
+            state: WindowState::from_placement(&placement),
+            z_order,
+            owner: Self::get_window_owner(hwnd),
+            visible: placement.visible,
+            cloaked: placement.cloaked,
+        }
+    }
+
+    // windows kept by filter described in one pass, in z-order from top to bottom
+    pub fn get_window_info_with(filter: FrontendFilter) -> Vec<WindowInfo> {
+        let monitors = Self::get_monitors();
+        Self::find_windows(filter)
+            .into_iter()
+            .enumerate()
+            .map(|(z_order, hwnd)| Self::describe_window_with(hwnd, &monitors, z_order))
+            .collect()
+    }
+
+    // show a minimized window the way it was before, maximized windows stay maximized
+    // and snapped windows go back to their snapped position
+    pub fn restore_window(hwnd: HWND) {
+        let placement = Self::get_placement(hwnd);
         unsafe {
-            let len = GetWindowTextW(hwnd, &mut BUFFER) as usize;
-            String::from_utf16_lossy(&BUFFER[0..len])
+            if !IsIconic(hwnd).as_bool() {
+                return;
+            }
+            if placement.flags.0 & WPF_RESTORETOMAXIMIZED.0 != 0 {
+                ShowWindow(hwnd, SW_SHOWMAXIMIZED);
+            } else {
+                ShowWindow(hwnd, SW_RESTORE);
+            }
         }
     }
 
@@ -76,8 +292,10 @@ impl WindowFinder {
     }
 
     pub fn get_window_info(hwnd: HWND) -> WINDOWINFO {
-        let mut window_info = WINDOWINFO::default();
-        window_info.cbSize = std::mem::size_of::<WINDOWINFO>() as u32;
+        let mut window_info = WINDOWINFO {
+            cbSize: std::mem::size_of::<WINDOWINFO>() as u32,
+            ..Default::default()
+        };
         unsafe {
             GetWindowInfo(hwnd, &mut window_info);
         }
@@ -92,19 +310,42 @@ impl WindowFinder {
         pid
     }
 
+    // empty if the process cannot be queried
     pub fn get_process_name_from_pid(pid: u32) -> String {
-        const MAX_FILENAME: usize = MAX_PATH as usize;
-        static mut BUFFER: [u16; MAX_FILENAME] = [0; MAX_FILENAME];
-        unsafe {
-            let mut len = 0;
-            if let Ok(handle) =
-                OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, BOOL::from(false), pid)
+        let handle =
+            match unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, BOOL::from(false), pid) }
             {
-                len = K32GetModuleFileNameExW(handle, HINSTANCE(0), &mut BUFFER) as usize;
-                CloseHandle(handle);
+                Ok(handle) => handle,
+                Err(_) => return String::new(),
+            };
+        let mut failed = false;
+        let path = read_wide_string(MAX_PATH as usize, |buffer| {
+            let mut len = buffer.len() as u32;
+            unsafe {
+                if QueryFullProcessImageNameW(
+                    handle,
+                    PROCESS_NAME_WIN32,
+                    PWSTR(buffer.as_mut_ptr()),
+                    &mut len,
+                )
+                .as_bool()
+                {
+                    return Some(len as usize);
+                }
+                if GetLastError() != ERROR_INSUFFICIENT_BUFFER {
+                    failed = true;
+                    return Some(0);
+                }
             }
-            String::from_utf16_lossy(&BUFFER[0..len]).replace("\\", "/")
+            None
+        });
+        unsafe {
+            CloseHandle(handle);
+        }
+        if failed {
+            return String::new();
         }
+        normalize_image_path(&path)
     }
 
     pub fn get_process_name_from_hwnd(hwnd: HWND) -> String {
@@ -116,65 +357,138 @@ impl WindowFinder {
         unsafe { GetForegroundWindow() }
     }
 
+    // cloaked is only read if asked for, it's false otherwise
+    pub fn get_window_candidate(hwnd: HWND, cloaked: bool) -> WindowCandidate {
+        let window_info = Self::get_window_info(hwnd);
+        let rect = window_info.rcWindow;
+        WindowCandidate {
+            hwnd,
+            owner: Self::get_window_owner(hwnd),
+            title_len: unsafe { GetWindowTextLengthW(hwnd) } as usize,
+            style: window_info.dwStyle,
+            ex_style: window_info.dwExStyle,
+            rect: Rect::new(rect.left, rect.top, rect.right, rect.bottom),
+            cloaked: cloaked && Self::is_window_cloaked(hwnd),
+        }
+    }
+
+    // windows kept by filter in z-order from top to bottom
+    pub fn find_windows<F: WindowFilter + 'static>(filter: F) -> Vec<HWND> {
+        let mut param = EnumWindowParam::new(filter);
+        WindowFinder::enum_window(&mut param);
+        param.window_handles
+    }
+
+    // topmost window kept by filter, enumeration stops there
+    pub fn find_window<F: WindowFilter + 'static>(filter: F) -> Option<HWND> {
+        let mut param = EnumWindowParam::new(filter).with_limit(1);
+        WindowFinder::enum_window(&mut param);
+        param.window_handles.first().copied()
+    }
+
     pub fn get_frontend_window() -> Vec<HWND> {
-        let mut param = EnumWindowParam::new(|_ewp, hwnd| {
-            let mut result = false;
-            #[allow(clippy::never_loop)]
-            loop {
-                unsafe {
-                    // window must have owner
-                    if GetWindow(hwnd, GW_OWNER) != HWND(0) {
-                        break;
-                    }
-
-                    // window title is not empty
-                    if GetWindowTextLengthW(hwnd) == 0 {
-                        break;
-                    }
-                }
+        Self::find_windows(FrontendFilter::default())
+    }
+}
 
-                let window_info = Self::get_window_info(hwnd);
+// longest string read from the system, the limit of `\\?\` paths
+const MAX_WIDE_STRING: usize = 32768;
 
-                let window_style = window_info.dwStyle;
+// read into a buffer of initial size, read returns the length of the string
+// or None if the buffer was too small, it's then doubled up to MAX_WIDE_STRING
+fn read_wide_string<F: FnMut(&mut [u16]) -> Option<usize>>(initial: usize, mut read: F) -> String {
+    let mut size = initial.clamp(1, MAX_WIDE_STRING);
+    loop {
+        let mut buffer = vec![0u16; size];
+        match read(&mut buffer) {
+            Some(len) => return String::from_utf16_lossy(&buffer[..len.min(size)]),
+            None if size >= MAX_WIDE_STRING => {
+                let len = buffer.iter().position(|c| *c == 0).unwrap_or(size);
+                return String::from_utf16_lossy(&buffer[..len]);
+            }
+            None => size = (size * 2).min(MAX_WIDE_STRING),
+        }
+    }
+}
 
-                // window is not pop-up window.
-                if window_style & WS_POPUP.0 != 0 {
-                    break;
-                }
-                // window should to be visible
-                if window_style & WS_VISIBLE.0 == 0 {
-                    break;
-                }
+// `\\?\C:\dir\app.exe` to `C:/dir/app.exe`, `\\?\UNC\server\share` to `//server/share`
+pub fn normalize_image_path(path: &str) -> String {
+    let path = if let Some(unc) = path.strip_prefix("\\\\?\\UNC\\") {
+        format!("\\\\{}", unc)
+    } else {
+        path.strip_prefix("\\\\?\\").unwrap_or(path).to_string()
+    };
+    path.replace('\\', "/")
+}
 
-                let window_extend_style = window_info.dwExStyle;
+#[cfg(test)]
+mod tests {
+    use windows::Win32::Foundation::HWND;
 
-                // window is not floating window
-                if window_extend_style & WS_EX_TOOLWINDOW.0 != 0 {
-                    break;
-                }
+    use crate::utils::{HasTitle, WindowCandidate};
 
-                let window_rect = window_info.rcWindow;
+    use super::{
+        normalize_image_path, read_wide_string, EnumWindowParam, WindowFinder, MAX_WIDE_STRING,
+    };
 
-                // window area must great a 4px
-                if window_rect.right - window_rect.left <= 1
-                    || window_rect.bottom - window_rect.top <= 1
-                {
-                    break;
-                }
+    #[test]
+    fn it_works() {
+        assert_eq!(
+            normalize_image_path("C:\\Windows\\explorer.exe"),
+            "C:/Windows/explorer.exe"
+        );
+        assert_eq!(
+            normalize_image_path("\\\\?\\C:\\very\\long\\app.exe"),
+            "C:/very/long/app.exe"
+        );
+        assert_eq!(
+            normalize_image_path("\\\\?\\UNC\\server\\share\\app.exe"),
+            "//server/share/app.exe"
+        );
+    }
 
-                result = true;
-                break;
+    #[test]
+    fn read_long_string() {
+        let long: Vec<u16> = "a".repeat(1000).encode_utf16().collect();
+        let mut reads = 0;
+        let value = read_wide_string(260, |buffer| {
+            reads += 1;
+            if buffer.len() < long.len() {
+                return None;
             }
-            result
+            buffer[..long.len()].copy_from_slice(&long);
+            Some(long.len())
         });
-        WindowFinder::enum_window(&mut param);
-        param.window_handles
+        assert_eq!(value.len(), 1000);
+        assert_eq!(reads, 3);
+        let value = read_wide_string(0, |buffer| {
+            buffer.fill('a' as u16);
+            None
+        });
+        assert_eq!(value.len(), MAX_WIDE_STRING);
+        // truncated at the limit, up to the terminator
+        let value = read_wide_string(MAX_WIDE_STRING, |buffer| {
+            buffer[..3].fill('a' as u16);
+            None
+        });
+        assert_eq!(value, "aaa");
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::WindowFinder;
+    #[test]
+    fn early_termination() {
+        let window = |id: isize, title_len: usize| WindowCandidate {
+            hwnd: HWND(id),
+            title_len,
+            ..Default::default()
+        };
+        let mut param = EnumWindowParam::new(HasTitle).with_limit(2);
+        assert!(!param.filter(&window(1, 0)));
+        assert!(param.filter(&window(2, 3)));
+        assert!(param.continue_enum);
+        assert!(param.filter(&window(3, 3)));
+        assert!(!param.continue_enum);
+        assert_eq!(param.get_window_handles(), &vec![HWND(2), HWND(3)]);
+    }
 
     #[test]
     #[ignore]
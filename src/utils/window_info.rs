@@ -0,0 +1,198 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use windows::Win32::Foundation::HWND;
+
+use super::WindowState;
+
+// what is known about a window, collected at once when the window system is enumerated
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct WindowInfo {
+    #[serde(with = "hwnd_serde")]
+    pub hwnd: HWND,
+    pub title: String,
+    pub class: String,
+    pub pid: u32,
+    // full image path of the owning process with `/` separators
+    pub path: String,
+    pub rect: Rect,
+    // 1-based index of the monitor showing most of the window, 0 if unknown
+    pub monitor: u32,
+    pub state: WindowState,
+    // position from the top of z-order when enumerated
+    pub z_order: usize,
+    #[serde(with = "hwnd_serde")]
+    pub owner: HWND,
+    pub visible: bool,
+    pub cloaked: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl Rect {
+    pub fn new(left: i32, top: i32, right: i32, bottom: i32) -> Self {
+        Rect {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    pub fn get_width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    pub fn get_height(&self) -> i32 {
+        self.bottom - self.top
+    }
+
+    pub fn get_area(&self) -> i64 {
+        self.get_width().max(0) as i64 * self.get_height().max(0) as i64
+    }
+}
+
+impl WindowInfo {
+    pub fn new(hwnd: HWND, path: &str, title: &str, class: &str) -> Self {
+        WindowInfo {
+            hwnd,
+            path: path.to_string(),
+            title: title.to_string(),
+            class: class.to_string(),
+            visible: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn get_hwnd(&self) -> HWND {
+        self.hwnd
+    }
+
+    pub fn get_pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub fn get_path(&self) -> &String {
+        &self.path
+    }
+
+    pub fn get_title(&self) -> &String {
+        &self.title
+    }
+
+    pub fn get_class(&self) -> &String {
+        &self.class
+    }
+
+    pub fn get_monitor(&self) -> u32 {
+        self.monitor
+    }
+
+    pub fn get_rect(&self) -> &Rect {
+        &self.rect
+    }
+
+    pub fn get_state(&self) -> WindowState {
+        self.state
+    }
+
+    pub fn get_z_order(&self) -> usize {
+        self.z_order
+    }
+
+    pub fn get_owner(&self) -> HWND {
+        self.owner
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn is_cloaked(&self) -> bool {
+        self.cloaked
+    }
+
+    // file name of the process image, like `chrome.exe`
+    pub fn get_basename(&self) -> &str {
+        self.path.rsplit(['/', '\\']).next().unwrap_or_default()
+    }
+}
+
+// one line in query syntax, so terms can be copied into a query
+impl Display for WindowInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let quote =
+            |value: &str| format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+        write!(
+            f,
+            "{:#010x} monitor:{} exe:{} class:{} title:{} path:{}",
+            self.hwnd.0,
+            self.monitor,
+            quote(self.get_basename()),
+            quote(&self.class),
+            quote(&self.title),
+            quote(&self.path)
+        )
+    }
+}
+
+// handles are plain numbers in serialized snapshots
+mod hwnd_serde {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use windows::Win32::Foundation::HWND;
+
+    pub fn serialize<S: Serializer>(hwnd: &HWND, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(hwnd.0 as i64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HWND, D::Error> {
+        i64::deserialize(deserializer).map(|value| HWND(value as isize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use windows::Win32::Foundation::HWND;
+
+    use crate::utils::WindowState;
+
+    use super::WindowInfo;
+
+    #[test]
+    fn it_works() {
+        let mut window = WindowInfo::new(
+            HWND(0x1a2b),
+            "C:/Program Files/App/App.exe",
+            "say \"hi\"",
+            "AppWindow",
+        );
+        window.monitor = 2;
+        assert_eq!(window.get_basename(), "App.exe");
+        assert_eq!(
+            window.to_string(),
+            r#"0x00001a2b monitor:2 exe:"App.exe" class:"AppWindow" title:"say \"hi\"" path:"C:/Program Files/App/App.exe""#
+        );
+    }
+
+    #[test]
+    fn serialize() {
+        let mut window = WindowInfo::new(HWND(0x1a2b), "C:/App.exe", "App", "AppWindow");
+        window.state = WindowState::Maximized;
+        let value = serde_json::to_value(&window).unwrap();
+        assert_eq!(value["hwnd"], json!(0x1a2b));
+        assert_eq!(value["state"], json!("maximized"));
+        assert_eq!(
+            value["rect"],
+            json!({ "left": 0, "top": 0, "right": 0, "bottom": 0 })
+        );
+        let parsed: WindowInfo = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed, window);
+    }
+}
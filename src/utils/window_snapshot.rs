@@ -0,0 +1,131 @@
+use std::ops::Index;
+
+use serde::{Deserialize, Serialize};
+use windows::Win32::Foundation::HWND;
+
+use super::WindowInfo;
+
+// windows enumerated at one moment, in z-order from top to bottom unless sorted otherwise
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct WindowSnapshot {
+    windows: Vec<WindowInfo>,
+}
+
+impl WindowSnapshot {
+    pub fn new(windows: Vec<WindowInfo>) -> Self {
+        WindowSnapshot { windows }
+    }
+
+    pub fn len(&self) -> usize {
+        self.windows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, WindowInfo> {
+        self.windows.iter()
+    }
+
+    pub fn get(&self, hwnd: HWND) -> Option<&WindowInfo> {
+        self.windows.iter().find(|window| window.hwnd == hwnd)
+    }
+
+    pub fn contains(&self, hwnd: HWND) -> bool {
+        self.get(hwnd).is_some()
+    }
+
+    pub fn get_hwnds(&self) -> Vec<HWND> {
+        self.windows.iter().map(|window| window.hwnd).collect()
+    }
+
+    pub fn filter<P: FnMut(&WindowInfo) -> bool>(self, mut predicate: P) -> Self {
+        WindowSnapshot {
+            windows: self
+                .windows
+                .into_iter()
+                .filter(|window| predicate(window))
+                .collect(),
+        }
+    }
+
+    // sorting is stable, windows which compare equal keep their z-order
+    pub fn sort_by_key<K: Ord, F: FnMut(&WindowInfo) -> K>(mut self, key: F) -> Self {
+        self.windows.sort_by_key(key);
+        self
+    }
+
+    pub fn sort_by_z_order(self) -> Self {
+        self.sort_by_key(|window| window.z_order)
+    }
+
+    pub fn sort_by_title(self) -> Self {
+        self.sort_by_key(|window| window.title.to_lowercase())
+    }
+
+    pub fn sort_by_path(self) -> Self {
+        self.sort_by_key(|window| window.path.to_lowercase())
+    }
+
+    pub fn into_vec(self) -> Vec<WindowInfo> {
+        self.windows
+    }
+}
+
+impl Index<usize> for WindowSnapshot {
+    type Output = WindowInfo;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.windows[index]
+    }
+}
+
+impl IntoIterator for WindowSnapshot {
+    type Item = WindowInfo;
+    type IntoIter = std::vec::IntoIter<WindowInfo>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.windows.into_iter()
+    }
+}
+
+impl FromIterator<WindowInfo> for WindowSnapshot {
+    fn from_iter<T: IntoIterator<Item = WindowInfo>>(iter: T) -> Self {
+        WindowSnapshot::new(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use windows::Win32::Foundation::HWND;
+
+    use crate::utils::{FakeBackend, WindowBackend};
+
+    #[test]
+    fn it_works() {
+        let mut backend = FakeBackend::new();
+        let code = backend.add_window(1, "C:/code.exe");
+        backend.set_window_title(code, "main.rs", "");
+        let term = backend.add_window(2, "C:/term.exe");
+        backend.set_window_title(term, "Admin", "");
+        let short = backend.add_window(3, "C:/code.exe");
+        backend.set_window_title(short, "A", "");
+        let snapshot = backend.snapshot();
+        assert_eq!(snapshot.len(), 3);
+        assert_eq!(snapshot[2].get_z_order(), 2);
+        assert_eq!(snapshot.get(term).unwrap().get_pid(), 2);
+
+        let code = snapshot
+            .clone()
+            .filter(|window| window.get_basename() == "code.exe");
+        assert_eq!(code.get_hwnds(), vec![HWND(1), HWND(3)]);
+        assert!(!code.contains(term));
+
+        let sorted = snapshot.sort_by_title();
+        assert_eq!(sorted.get_hwnds(), vec![HWND(3), HWND(2), HWND(1)]);
+        let sorted = sorted.sort_by_z_order();
+        assert_eq!(sorted.get_hwnds(), vec![HWND(1), HWND(2), HWND(3)]);
+    }
+}
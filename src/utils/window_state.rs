@@ -0,0 +1,148 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::Rect;
+
+// how a window is currently shown
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowState {
+    #[default]
+    Normal,
+    Minimized,
+    Maximized,
+    // borderless window covering its whole monitor
+    Fullscreen,
+    // docked to a part of the monitor, its rect differs from the restored placement
+    Snapped,
+    // not visible
+    Hidden,
+    // visible but not shown by the compositor, like windows on another virtual desktop
+    Cloaked,
+}
+
+// raw facts about a window the state is derived from
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WindowPlacement {
+    pub visible: bool,
+    pub cloaked: bool,
+    pub minimized: bool,
+    pub maximized: bool,
+    // window has a caption or a sizing border
+    pub framed: bool,
+    pub rect: Rect,
+    // rect the window returns to when restored
+    pub normal_rect: Rect,
+    pub monitor_rect: Rect,
+}
+
+impl WindowState {
+    pub fn from_placement(placement: &WindowPlacement) -> Self {
+        if !placement.visible {
+            WindowState::Hidden
+        } else if placement.cloaked {
+            WindowState::Cloaked
+        } else if placement.minimized {
+            WindowState::Minimized
+        } else if placement.maximized {
+            WindowState::Maximized
+        } else if !placement.framed && placement.rect == placement.monitor_rect {
+            WindowState::Fullscreen
+        } else if placement.rect != placement.normal_rect {
+            WindowState::Snapped
+        } else {
+            WindowState::Normal
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            WindowState::Normal => "normal",
+            WindowState::Minimized => "minimized",
+            WindowState::Maximized => "maximized",
+            WindowState::Fullscreen => "fullscreen",
+            WindowState::Snapped => "snapped",
+            WindowState::Hidden => "hidden",
+            WindowState::Cloaked => "cloaked",
+        }
+    }
+
+    // window is on screen, can be looked at without restoring it first
+    pub fn is_shown(&self) -> bool {
+        !matches!(
+            self,
+            WindowState::Minimized | WindowState::Hidden | WindowState::Cloaked
+        )
+    }
+}
+
+impl Display for WindowState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::Rect;
+
+    use super::{WindowPlacement, WindowState};
+
+    #[test]
+    fn it_works() {
+        let monitor = Rect::new(0, 0, 1920, 1080);
+        let normal = WindowPlacement {
+            visible: true,
+            framed: true,
+            rect: Rect::new(100, 100, 900, 700),
+            normal_rect: Rect::new(100, 100, 900, 700),
+            monitor_rect: monitor,
+            ..Default::default()
+        };
+        assert_eq!(WindowState::from_placement(&normal), WindowState::Normal);
+        let snapped = WindowPlacement {
+            rect: Rect::new(0, 0, 960, 1040),
+            ..normal
+        };
+        assert_eq!(WindowState::from_placement(&snapped), WindowState::Snapped);
+        let fullscreen = WindowPlacement {
+            framed: false,
+            rect: monitor,
+            ..normal
+        };
+        assert_eq!(
+            WindowState::from_placement(&fullscreen),
+            WindowState::Fullscreen
+        );
+        let maximized = WindowPlacement {
+            maximized: true,
+            rect: monitor,
+            ..normal
+        };
+        assert_eq!(
+            WindowState::from_placement(&maximized),
+            WindowState::Maximized
+        );
+        let minimized = WindowPlacement {
+            minimized: true,
+            ..maximized
+        };
+        assert_eq!(
+            WindowState::from_placement(&minimized),
+            WindowState::Minimized
+        );
+        let cloaked = WindowPlacement {
+            cloaked: true,
+            ..normal
+        };
+        assert_eq!(WindowState::from_placement(&cloaked), WindowState::Cloaked);
+        let hidden = WindowPlacement {
+            visible: false,
+            ..cloaked
+        };
+        assert_eq!(WindowState::from_placement(&hidden), WindowState::Hidden);
+        assert!(!WindowState::Minimized.is_shown());
+        assert!(WindowState::Snapped.is_shown());
+    }
+}